@@ -7,7 +7,7 @@ use tui::{
     Frame,
 };
 
-use crate::game_data::{ClickAction, GameData, Status};
+use crate::game_data::{ClickAction, Focus, GameData, Status, Toggle};
 
 fn centered(r: Rect, size: (u16, u16)) -> Rect {
     let solution_vert_layout = Layout::default()
@@ -45,14 +45,23 @@ where
 
     let centered_layout = centered(size, (19, 4));
 
+    let warnings = gd.check_consistency();
+    let message = match &gd.message {
+        Some(message) => Some(("Explanation", message.clone(), Color::LightCyan)),
+        None if !warnings.is_empty() => Some(("Warnings", warnings.join(" / "), Color::Yellow)),
+        None => None,
+    };
+    let message_height = if message.is_some() { 3 } else { 0 };
+
     let general_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(3), // Title
-                Constraint::Length(3), // Rules
-                Constraint::Min(10),   // Criterias + tries
-                Constraint::Length(3), // Command line
+                Constraint::Length(3),              // Title
+                Constraint::Length(3),              // Rules
+                Constraint::Min(10),                // Criterias + tries
+                Constraint::Length(message_height), // Explanation or warnings
+                Constraint::Length(3),              // Command line
             ]
             .as_ref(),
         )
@@ -74,6 +83,7 @@ where
         .constraints(
             [
                 Constraint::Min(10),                                           // tries
+                Constraint::Length(3),                                         // status
                 Constraint::Length(2 + 1 + gd.game.configuration.base as u16), // strikes
             ]
             .as_ref(),
@@ -105,7 +115,23 @@ where
     render_criterias(frame, gd, game_layout[0]);
 
     render_tries(frame, gd, tries_strikes_layout[0]);
-    render_strikes(frame, gd, tries_strikes_layout[1]);
+    let remaining = match gd.remaining_candidates() {
+        Ok(1) => "1 code left".to_string(),
+        Ok(count) => format!("{count} codes left"),
+        Err(e) => format!("Codes left unknown : {e}"),
+    };
+    render_block_with_title(
+        frame,
+        tries_strikes_layout[1],
+        "Status",
+        &remaining,
+        Color::White,
+    );
+    render_strikes(frame, gd, tries_strikes_layout[2]);
+
+    if let Some((title, text, color)) = message {
+        render_block_with_title(frame, general_layout[3], title, &text, color);
+    }
 
     let command_line_color = match gd.command_status {
         Status::None => Color::DarkGray,
@@ -115,15 +141,23 @@ where
 
     render_block_with_title(
         frame,
-        general_layout[3],
-        "Command line : t(est) <code> <crits>, b(id) <solution>, s(trike) <columns> <values>, q(uit)",
+        general_layout[4],
+        "Command line : t(est) <code> <crits>, b(id) <solution>, s(trike) <columns> <values>, save/load <file>, layout auto|cols|rows <n>, digits asc|desc, hint, explain <code> <crit>/reveal (practice), reveal <crit>, undo/redo, q(uit), Tab + arrows/space to play by keyboard",
         &gd.command_line,
         command_line_color,
     );
 
     if let Some(val) = gd.solution {
         let (color, mut text) = match val {
-            true => (Color::Green, "Well done!".to_string()),
+            true if gd.revealed_criterias.is_empty() => (Color::Green, "Well done!".to_string()),
+            true => (
+                Color::Green,
+                format!("Well done, with {} reveal(s)!", gd.revealed_criterias.len()),
+            ),
+            false if gd.is_lost() => (
+                Color::Red,
+                format!("No tests left! The code was {}", gd.game.code),
+            ),
             false => (Color::Red, "You failed!".to_string()),
         };
 
@@ -169,21 +203,26 @@ where
 {
     let mut rows = Vec::new();
 
-    for (y, row) in gd.striked.iter().enumerate() {
+    let base = gd.game.configuration.base;
+    for y in 0..base as usize {
+        let line = gd.strike_line(gd.strike_orientation.value(y, base));
         let mut columns: Vec<Cell> = Vec::new();
 
-        for (x, (value, _striked)) in row.iter().enumerate() {
-            let style = match _striked {
+        for (x, (value, _striked)) in gd.striked[line].iter().enumerate() {
+            let mut style = match _striked {
                 true => Style::default()
                     .bg(Color::Red)
                     .add_modifier(Modifier::CROSSED_OUT),
                 false => Style::default().bg(Color::Green),
             };
+            if gd.focus == Focus::Cell(Toggle::Strike(line, x)) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
             columns.push(Cell::from(Span::styled(value.to_string(), style)));
 
             let cell_rect = Rect::new(rect.x + 1 + x as u16, rect.y + 2 + y as u16, 1, 1);
             gd.click_areas
-                .push((cell_rect, ClickAction::ToggleStrike(x as u16, y as u16)));
+                .push((cell_rect, ClickAction::ToggleStrike(x as u16, line as u16)));
         }
 
         rows.push(Row::new(columns));
@@ -219,8 +258,7 @@ where
     B: Backend,
 {
     let crit_count = gd.game.criterias.len();
-    let crit_grid_x = ((crit_count as f64 - 1.0).sqrt() as usize) + 1;
-    let crit_grid_y = ((crit_count - 1) / crit_grid_x) + 1;
+    let (crit_grid_x, crit_grid_y) = gd.criteria_layout.grid(crit_count);
     let mut constraints_y = Vec::new();
     for _ in 0..crit_grid_y {
         constraints_y.push(Constraint::Ratio(1, crit_grid_y as u32));
@@ -260,10 +298,17 @@ where
                 true => Color::Green,
                 false => Color::Red,
             };
-            rows.push(Row::new(vec![Cell::from(Span::styled(
-                rule.to_string(),
-                Style::default().fg(color),
-            ))]));
+            let mut style = Style::default().fg(color);
+            if gd.focus == Focus::Cell(Toggle::CriteriaRule(crit_id, rule_id)) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let mut text = rule.to_string();
+            let revealed = (gd.practice && gd.revealed) || gd.revealed_criterias.contains(&crit.id);
+            if revealed && *rule == crit.verif.rule {
+                text.push_str(" (*)");
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            rows.push(Row::new(vec![Cell::from(Span::styled(text, style))]));
 
             gd.click_areas.push((
                 cell_rect,
@@ -276,7 +321,7 @@ where
             Style::default().add_modifier(Modifier::BOLD),
         ))]);
 
-        let crit_title = format!("Criteria {}", crit_id.clone());
+        let crit_title = format!("Criteria {}", crit.id);
         let table = Table::new(rows)
             .header(header)
             .block(
@@ -294,18 +339,20 @@ where
     }
 }
 
-fn draw_tries(gd: &GameData) -> Table {
+fn draw_tries(gd: &GameData) -> Table<'_> {
     let mut rows = Vec::new();
 
     for log in gd.logs.iter() {
-        let color = match log.result {
-            true => Color::Green,
-            false => Color::Red,
+        let color = match (log.hint, log.result) {
+            (true, _) => Color::Yellow,
+            (false, true) => Color::Green,
+            (false, false) => Color::Red,
         };
 
-        let msg = match log.result {
-            true => "Right",
-            false => "Wrong",
+        let msg = match (log.hint, log.result) {
+            (true, _) => "Hint",
+            (false, true) => "Right",
+            (false, false) => "Wrong",
         }
         .to_owned();
 
@@ -336,7 +383,16 @@ fn draw_tries(gd: &GameData) -> Table {
                 .border_style(Style::default())
                 .style(Style::default())
                 .border_type(BorderType::Plain)
-                .title("Tries"),
+                .title(
+                    match (gd.game.configuration.max_attempts, gd.reveal_budget) {
+                        (Some(max), 0) => format!("Tries {}/{max}", gd.attempts()),
+                        (Some(max), reveals) => {
+                            format!("Tries {}/{max}, {reveals} reveal(s) left", gd.attempts())
+                        }
+                        (None, 0) => "Tries".to_string(),
+                        (None, reveals) => format!("Tries, {reveals} reveal(s) left"),
+                    },
+                ),
         )
         .widths(&[
             Constraint::Length(5),
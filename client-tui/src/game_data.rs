@@ -1,13 +1,38 @@
-use crossterm::event::{KeyCode, MouseButton};
-use enigmind_lib::setup::Game;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton};
+use enigmind_lib::{
+    code::Code, column::Column, criteria::Criteria, error::EnigmindError, setup::Game,
+    solver::TestResult,
+};
+use serde::{Deserialize, Serialize};
 use tui::{layout::Rect, style::Color};
 
-use crate::input::{Events, InputEvent};
+use crate::{
+    hint::Deductions,
+    input::{Events, InputEvent},
+};
+
+const HISTORY_SIZE: usize = 100;
+
+/// Every command understood by the command line, in completion order.
+const COMMANDS: [&str; 13] = [
+    "b", "digits", "explain", "hint", "layout", "load", "q", "redo", "reveal", "s", "save", "t",
+    "undo",
+];
 
+#[derive(Serialize, Deserialize)]
 pub struct GameLog {
     pub code: String,
     pub crit_index: u8,
     pub result: bool,
+    #[serde(default)]
+    pub hint: bool,
 }
 
 impl GameLog {
@@ -16,12 +41,23 @@ impl GameLog {
             code: code.to_string(),
             crit_index,
             result: res,
+            hint: false,
+        }
+    }
+
+    pub fn hint(code: &str, crit_index: u8) -> Self {
+        Self {
+            code: code.to_string(),
+            crit_index,
+            result: false,
+            hint: true,
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub enum Status {
+    #[default]
     None,
     Valid,
     Error,
@@ -44,24 +80,140 @@ pub enum ClickAction {
     CloseSolutionWidget,
 }
 
+/// A grid cell the player can toggle, either in the strike grid or in the criterias.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle {
+    Strike(usize, usize),
+    CriteriaRule(usize, usize),
+}
+
+/// How the criterias are laid out on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CriteriaLayout {
+    /// Near-square grid
+    #[default]
+    Auto,
+    Columns(u16),
+    Rows(u16),
+}
+
+impl CriteriaLayout {
+    /// Number of columns and lines of the grid holding `count` criterias.
+    pub fn grid(&self, count: usize) -> (usize, usize) {
+        let count = count.max(1);
+        match *self {
+            CriteriaLayout::Auto => {
+                let columns = ((count as f64 - 1.0).sqrt() as usize) + 1;
+                (columns, (count - 1) / columns + 1)
+            }
+            CriteriaLayout::Columns(columns) => {
+                let columns = (columns as usize).clamp(1, count);
+                (columns, (count - 1) / columns + 1)
+            }
+            CriteriaLayout::Rows(lines) => {
+                let lines = (lines as usize).clamp(1, count);
+                ((count - 1) / lines + 1, lines)
+            }
+        }
+    }
+}
+
+/// Order of the digits down the strike grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrikeOrientation {
+    /// Highest digit on the top line
+    #[default]
+    Descending,
+    Ascending,
+}
+
+impl StrikeOrientation {
+    /// Digit shown on a line of the strike grid.
+    pub fn value(&self, line: usize, base: u8) -> u8 {
+        match self {
+            StrikeOrientation::Descending => base - 1 - line as u8,
+            StrikeOrientation::Ascending => line as u8,
+        }
+    }
+
+    /// Line of the strike grid showing a digit.
+    pub fn line(&self, value: u8, base: u8) -> usize {
+        match self {
+            StrikeOrientation::Descending => (base - 1 - value) as usize,
+            StrikeOrientation::Ascending => value as usize,
+        }
+    }
+}
+
+/// Where keyboard input goes: the command line, or a cell of one of the grids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    CommandLine,
+    Cell(Toggle),
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct GameData {
     pub game: Game,
     pub logs: Vec<GameLog>,
+    #[serde(skip)]
     pub command_line: String,
-    pub last_command_line: String,
+    #[serde(skip)]
+    history: VecDeque<String>,
+    // Position in the history while scrolling, and the line being typed before scrolling
+    #[serde(skip)]
+    history_index: Option<usize>,
+    #[serde(skip)]
+    draft: String,
+    // Candidates being cycled through by Tab, and the one currently shown
+    #[serde(skip)]
+    completion: Option<(Vec<String>, usize)>,
+    #[serde(skip)]
     pub command_status: Status,
+    #[serde(skip)]
     pub quit: bool,
+    // Always stored highest digit first, the orientation only changes the display
     pub striked: Vec<Vec<(char, bool)>>,
+    #[serde(skip)]
+    pub strike_orientation: StrikeOrientation,
     pub solution: Option<bool>,
     pub criterias_state: Vec<Vec<bool>>,
+    #[serde(skip)]
     pub click_areas: Vec<(Rect, ClickAction)>,
+    #[serde(skip)]
+    pub criteria_layout: CriteriaLayout,
+    #[serde(skip)]
+    pub focus: Focus,
+    /// Enables the commands revealing what the player should deduce
+    #[serde(skip)]
+    pub practice: bool,
+    #[serde(skip)]
+    pub revealed: bool,
+    /// Number of verifiers the player may still reveal
+    #[serde(default)]
+    pub reveal_budget: u8,
+    #[serde(default)]
+    pub revealed_criterias: Vec<u8>,
+    #[serde(skip)]
+    pub message: Option<String>,
+    // Results of the code and criteria pairs already logged, rebuilt from the logs on load
+    #[serde(skip)]
+    tested: HashMap<(Code, u8), bool>,
+    // Tests and bids are facts the player learnt, so only toggles can be undone
+    #[serde(skip)]
+    undo_stack: Vec<Vec<(Toggle, bool)>>,
+    #[serde(skip)]
+    redo_stack: Vec<Vec<(Toggle, bool)>>,
 }
 
 impl GameData {
     pub fn new(game: Game) -> Self {
+        let base = game.configuration.base;
         let mut striked = Vec::new();
-        for i in (0..game.configuration.base).rev() {
-            let val = i.to_string().chars().nth(0).unwrap();
+        for line in 0..base as usize {
+            let value = StrikeOrientation::Descending.value(line, base);
+            let val = value.to_string().chars().nth(0).unwrap();
             let line = vec![(val, false); game.configuration.column_count as usize];
             striked.push(line);
         }
@@ -76,35 +228,173 @@ impl GameData {
             game,
             logs: Vec::new(),
             command_line: String::new(),
-            last_command_line: String::new(),
+            history: VecDeque::new(),
+            history_index: None,
+            draft: String::new(),
+            completion: None,
             command_status: Status::None,
             quit: false,
             striked,
+            strike_orientation: StrikeOrientation::Descending,
             solution: None,
             click_areas: Vec::new(),
             criterias_state,
+            criteria_layout: CriteriaLayout::Auto,
+            focus: Focus::CommandLine,
+            practice: false,
+            revealed: false,
+            reveal_budget: 0,
+            revealed_criterias: Vec::new(),
+            message: None,
+            tested: HashMap::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         }
     }
 
-    pub fn handle_events(&mut self, events: &Events) {
-        match events.next().unwrap() {
-            InputEvent::Input(key_event) => match key_event.code {
-                KeyCode::Esc => self.quit = true,
-                KeyCode::Char(c) => {
-                    self.command_line.push(c);
-                    self.command_status = Status::None
-                }
-                KeyCode::Backspace => {
-                    self.command_line.pop();
-                    self.command_status = Status::None;
+    /// Line of `striked` holding a digit.
+    pub fn strike_line(&self, value: u8) -> usize {
+        StrikeOrientation::Descending.line(value, self.game.configuration.base)
+    }
+
+    fn cell(&mut self, toggle: Toggle) -> &mut bool {
+        match toggle {
+            Toggle::Strike(line, column) => &mut self.striked[line][column].1,
+            Toggle::CriteriaRule(crit, rule) => &mut self.criterias_state[crit][rule],
+        }
+    }
+
+    pub fn toggle(&mut self, toggles: &[Toggle]) {
+        let mut entry = Vec::new();
+        for toggle in toggles {
+            let cell = self.cell(*toggle);
+            entry.push((*toggle, *cell));
+            *cell ^= true;
+        }
+
+        self.undo_stack.push(entry);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) -> Status {
+        match self.undo_stack.pop() {
+            Some(entry) => {
+                for (toggle, previous) in entry.iter().rev() {
+                    *self.cell(*toggle) = *previous;
                 }
-                KeyCode::Up => self.command_line = self.last_command_line.clone(),
-                KeyCode::Enter => {
-                    self.solution = None;
-                    self.process_commands()
+                self.redo_stack.push(entry);
+                Status::Valid
+            }
+            None => Status::Error,
+        }
+    }
+
+    pub fn redo(&mut self) -> Status {
+        match self.redo_stack.pop() {
+            Some(entry) => {
+                for (toggle, previous) in entry.iter() {
+                    *self.cell(*toggle) = !previous;
                 }
-                _ => (),
-            },
+                self.undo_stack.push(entry);
+                Status::Valid
+            }
+            None => Status::Error,
+        }
+    }
+
+    /// Contradictions between the strike grid, the enabled criteria rules and the test results.
+    pub fn check_consistency(&self) -> Vec<String> {
+        let deductions = Deductions::new(self);
+        let mut warnings = Vec::new();
+
+        for crit_id in deductions.unsatisfiable_criterias() {
+            warnings.push(format!(
+                "Criteria {crit_id} : no enabled rule matches the tests"
+            ));
+        }
+
+        for (x, value) in deductions.required_digits() {
+            if self.striked[self.strike_line(value)][x].1 {
+                warnings.push(format!(
+                    "{}{value} is struck but every code matching the tests has it",
+                    Column::from(x as u8)
+                ));
+            }
+        }
+
+        if deductions.candidate_count() == 0 {
+            warnings.push("No code left matches the strikes and the tests".to_string());
+        }
+
+        warnings
+    }
+
+    /// Number of codes consistent with the criteria and the logged test results, whatever the
+    /// strikes and the enabled rules, hints teaching nothing.
+    /// Fails on a log whose code or criteria isn't part of the game, as in a corrupt save.
+    pub fn remaining_candidates(&self) -> Result<usize, EnigmindError> {
+        let configuration = &self.game.configuration;
+        let results = self
+            .logs
+            .iter()
+            .filter(|log| !log.hint)
+            .map(|log| {
+                Ok(TestResult {
+                    code: configuration.parse_code(&log.code)?,
+                    criterion: log.crit_index,
+                    passed: log.result,
+                })
+            })
+            .collect::<Result<Vec<TestResult>, EnigmindError>>()?;
+        Ok(self.game.candidates_after(&results)?.len())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<GameData> {
+        let mut gd: GameData = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        let configuration = &gd.game.configuration;
+        if let Err(e) = configuration.check_schema_version() {
+            bail!("Saved game can't be read : {e}");
+        }
+        if gd.striked.len() != configuration.base as usize
+            || gd
+                .striked
+                .iter()
+                .any(|line| line.len() != configuration.column_count as usize)
+            || gd.criterias_state.len() != gd.game.criterias.len()
+            || gd
+                .criterias_state
+                .iter()
+                .zip(gd.game.criterias.iter())
+                .any(|(state, crit)| state.len() != crit.rules.len())
+        {
+            bail!("Saved grids don't match the game configuration");
+        }
+
+        gd.tested = gd
+            .logs
+            .iter()
+            .filter(|log| !log.hint)
+            .filter_map(|log| {
+                let code = configuration.parse_code(&log.code).ok()?;
+                Some(((code, log.crit_index), log.result))
+            })
+            .collect();
+        Ok(gd)
+    }
+
+    pub fn handle_events(&mut self, events: &Events) {
+        self.handle_event(events.next().unwrap());
+    }
+
+    pub fn handle_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Input(key_event) => self.process_key(key_event),
             InputEvent::Click(mb, x, y) => self.process_click(mb, x, y),
             InputEvent::Tick => (),
         };
@@ -112,6 +402,183 @@ impl GameData {
 }
 
 impl GameData {
+    fn process_key(&mut self, key_event: KeyEvent) {
+        match (key_event.code, self.focus) {
+            (KeyCode::Esc, _) => self.quit = true,
+            (KeyCode::Char('z'), _) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.command_status = self.undo()
+            }
+            (KeyCode::Char('y'), _) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.command_status = self.redo()
+            }
+            (KeyCode::Tab, Focus::CommandLine) if !self.command_line.is_empty() => self.complete(),
+            (KeyCode::Tab, _) => self.focus = self.next_focus(),
+            (KeyCode::Char(' '), Focus::Cell(toggle)) => self.toggle(&[toggle]),
+            (KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right, Focus::Cell(toggle)) => {
+                self.focus = Focus::Cell(self.moved(toggle, key_event.code))
+            }
+            (KeyCode::Char(c), _) => {
+                self.command_line.push(c);
+                self.command_status = Status::None
+            }
+            (KeyCode::Backspace, _) => {
+                self.command_line.pop();
+                self.command_status = Status::None;
+            }
+            (KeyCode::Up, _) => self.history_previous(),
+            (KeyCode::Down, _) => self.history_next(),
+            (KeyCode::Enter, _) => {
+                self.solution = None;
+                self.process_commands()
+            }
+            _ => (),
+        }
+    }
+
+    /// Full command lines the last word of the command line can be completed to.
+    fn completions(&self) -> Vec<String> {
+        let mut args: Vec<&str> = self.command_line.split(' ').collect();
+        let word = args.pop().unwrap_or("");
+
+        let options: Vec<String> = match (args.first(), args.len()) {
+            (None, _) => COMMANDS.iter().map(|command| command.to_string()).collect(),
+            // Tests name each criteria with a single digit
+            (Some(&"t"), 2) => {
+                let typed: Vec<u32> = word.chars().filter_map(|crit| crit.to_digit(10)).collect();
+                self.game
+                    .criterias
+                    .iter()
+                    .map(|crit| crit.id as u32)
+                    .filter(|id| *id < 10 && !typed.contains(id))
+                    .map(|id| format!("{word}{id}"))
+                    .collect()
+            }
+            (Some(&"s"), _) if word.is_empty() => self
+                .game
+                .configuration
+                .get_all_columns()
+                .into_iter()
+                .map(|column| column.to_string())
+                .collect(),
+            (Some(&"digits"), 1) => ["asc", "desc"]
+                .iter()
+                .map(|order| order.to_string())
+                .collect(),
+            (Some(&"reveal"), 1) => self
+                .game
+                .criterias
+                .iter()
+                .map(|crit| crit.id)
+                .filter(|id| !self.revealed_criterias.contains(id))
+                .map(|id| id.to_string())
+                .collect(),
+            (Some(&"layout"), 1) => ["auto", "cols", "rows"]
+                .iter()
+                .map(|kind| kind.to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        options
+            .into_iter()
+            .filter(|option| option.starts_with(word))
+            .map(|option| match args.is_empty() {
+                true => option,
+                false => format!("{} {option}", args.join(" ")),
+            })
+            .collect()
+    }
+
+    /// Completes the command line to the only candidate, or cycles through candidates.
+    fn complete(&mut self) {
+        let (candidates, index) = match self.completion.take() {
+            Some((candidates, index)) if candidates[index] == self.command_line => {
+                let next = (index + 1) % candidates.len();
+                (candidates, next)
+            }
+            _ => (self.completions(), 0),
+        };
+
+        match candidates.len() {
+            0 => (),
+            1 if !candidates[0].contains(' ') => self.command_line = format!("{} ", candidates[0]),
+            1 => self.command_line = candidates[0].clone(),
+            _ => {
+                self.command_line = candidates[index].clone();
+                self.completion = Some((candidates, index));
+            }
+        }
+        self.command_status = Status::None;
+    }
+
+    fn history_previous(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.command_line.clone();
+                self.history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+        self.history_index = Some(index);
+        self.command_line = self.history[index].clone();
+    }
+
+    fn history_next(&mut self) {
+        match self.history_index {
+            Some(index) if index + 1 < self.history.len() => {
+                self.history_index = Some(index + 1);
+                self.command_line = self.history[index + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.command_line = std::mem::take(&mut self.draft);
+            }
+            None => (),
+        }
+    }
+
+    /// Cycles the focus from the command line to the criterias, then the strike grid.
+    fn next_focus(&self) -> Focus {
+        match self.focus {
+            Focus::CommandLine => Focus::Cell(Toggle::CriteriaRule(0, 0)),
+            Focus::Cell(Toggle::CriteriaRule(..)) => Focus::Cell(Toggle::Strike(0, 0)),
+            Focus::Cell(Toggle::Strike(..)) => Focus::CommandLine,
+        }
+    }
+
+    /// Moves a grid cell one step in the direction of an arrow key, wrapping around the edges.
+    /// Up and down go through the rules of a criteria, left and right through the criterias.
+    fn moved(&self, toggle: Toggle, key: KeyCode) -> Toggle {
+        let step = |index: usize, len: usize, forward: bool| match forward {
+            true => (index + 1) % len,
+            false => (index + len - 1) % len,
+        };
+
+        match (toggle, key) {
+            (Toggle::Strike(line, column), KeyCode::Up | KeyCode::Down) => {
+                // Lines are stored highest digit first, whatever is shown on top
+                let downwards = (key == KeyCode::Down)
+                    == (self.strike_orientation == StrikeOrientation::Descending);
+                Toggle::Strike(step(line, self.striked.len(), downwards), column)
+            }
+            (Toggle::Strike(line, column), _) => Toggle::Strike(
+                line,
+                step(column, self.striked[line].len(), key == KeyCode::Right),
+            ),
+            (Toggle::CriteriaRule(crit, rule), KeyCode::Up | KeyCode::Down) => {
+                Toggle::CriteriaRule(
+                    crit,
+                    step(rule, self.criterias_state[crit].len(), key == KeyCode::Down),
+                )
+            }
+            (Toggle::CriteriaRule(crit, rule), _) => {
+                let crit = step(crit, self.criterias_state.len(), key == KeyCode::Right);
+                Toggle::CriteriaRule(crit, rule.min(self.criterias_state[crit].len() - 1))
+            }
+        }
+    }
+
     fn process_click(&mut self, mb: MouseButton, x: u16, y: u16) {
         if mb == MouseButton::Left {
             for (rect, action) in self.click_areas.clone().into_iter().rev() {
@@ -127,17 +594,22 @@ impl GameData {
     fn process_click_action(&mut self, action: ClickAction) {
         match action {
             ClickAction::ToggleStrike(x, y) => {
-                self.striked[y as usize][x as usize].1 ^= true;
+                self.toggle(&[Toggle::Strike(y as usize, x as usize)])
             }
             ClickAction::ToggleCriteriaRule(crit, rule) => {
-                self.criterias_state[crit as usize][rule as usize] ^= true
+                self.toggle(&[Toggle::CriteriaRule(crit as usize, rule as usize)])
             }
             ClickAction::CloseSolutionWidget => self.solution = None,
         }
     }
 
     fn process_commands(&mut self) {
-        self.last_command_line = self.command_line.clone();
+        self.history.push_back(self.command_line.clone());
+        if self.history.len() > HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history_index = None;
+        self.message = None;
 
         let command = self.command_line.split(' ').next().unwrap();
 
@@ -146,6 +618,15 @@ impl GameData {
             "t" => self.process_test_command(),
             "b" => self.process_bid_command(),
             "s" => self.process_toggle_command(),
+            "save" => self.process_save_command(),
+            "load" => self.process_load_command(),
+            "digits" => self.process_digits_command(),
+            "hint" => self.process_hint_command(),
+            "explain" => self.process_explain_command(),
+            "reveal" => self.process_reveal_command(),
+            "layout" => self.process_layout_command(),
+            "undo" => self.undo(),
+            "redo" => self.redo(),
             _ => Status::Error,
         };
 
@@ -158,38 +639,164 @@ impl GameData {
         let mut args = self.command_line.split(' ');
         args.next();
 
-        for arg in args.clone() {
-            if arg.len() != 2 {
+        let mut toggles = Vec::new();
+        for arg in args {
+            let mut chars = arg.chars();
+            let (Some(column), Some(value), None) = (chars.next(), chars.next(), chars.next())
+            else {
                 return Status::Error;
-            }
-
-            let column_str = arg.chars().nth(0).unwrap().to_ascii_uppercase();
-            let value_str = arg.chars().nth(1).unwrap();
+            };
 
-            if !column_str.is_alphabetic() || !value_str.is_numeric() {
+            let Some(column_index) = self.game.to_column_index(column) else {
                 return Status::Error;
-            }
+            };
+            let value = match value.to_digit(10) {
+                Some(value) if self.game.is_value_compatible(value as u8) => value as u8,
+                _ => return Status::Error,
+            };
 
-            if !self.game.is_column_compatible(column_str) {
-                return Status::Error;
+            toggles.push(Toggle::Strike(
+                self.strike_line(value),
+                column_index as usize,
+            ));
+        }
+        self.toggle(&toggles);
+
+        Status::Valid
+    }
+
+    fn process_digits_command(&mut self) -> Status {
+        self.strike_orientation = match self.command_line.split(' ').nth(1) {
+            Some("asc") => StrikeOrientation::Ascending,
+            Some("desc") => StrikeOrientation::Descending,
+            _ => return Status::Error,
+        };
+        Status::Valid
+    }
+
+    fn process_layout_command(&mut self) -> Status {
+        let mut args = self.command_line.split(' ');
+        args.next();
+
+        let kind = args.next().unwrap_or("");
+        let count = args.next().and_then(|arg| arg.parse::<u16>().ok());
+
+        self.criteria_layout = match (kind, count) {
+            ("auto", None) => CriteriaLayout::Auto,
+            ("cols", Some(n)) if n > 0 => CriteriaLayout::Columns(n),
+            ("rows", Some(n)) if n > 0 => CriteriaLayout::Rows(n),
+            _ => return Status::Error,
+        };
+
+        Status::Valid
+    }
+
+    fn process_save_command(&mut self) -> Status {
+        match self.command_line.split(' ').nth(1) {
+            Some(path) if !path.is_empty() => match self.save(Path::new(path)) {
+                Ok(()) => Status::Valid,
+                Err(_) => Status::Error,
+            },
+            _ => Status::Error,
+        }
+    }
+
+    fn process_load_command(&mut self) -> Status {
+        match self.command_line.split(' ').nth(1) {
+            Some(path) if !path.is_empty() => match GameData::load(Path::new(path)) {
+                Ok(mut gd) => {
+                    gd.practice = self.practice;
+                    *self = gd;
+                    Status::Valid
+                }
+                Err(_) => Status::Error,
+            },
+            _ => Status::Error,
+        }
+    }
+
+    fn process_hint_command(&mut self) -> Status {
+        match Deductions::new(self).suggest() {
+            Some((code, crits)) => {
+                let code_str = code.to_string();
+                for crit_id in crits {
+                    self.logs.push(GameLog::hint(&code_str, crit_id));
+                }
+                Status::Valid
             }
+            None => Status::Error,
+        }
+    }
 
-            let value = value_str.to_digit(10).unwrap() as u8;
-            if !self.game.is_value_compatible(value) {
-                return Status::Error;
+    fn process_explain_command(&mut self) -> Status {
+        if !self.practice {
+            return Status::Error;
+        }
+
+        let mut args = self.command_line.split(' ');
+        args.next();
+        let code = match self
+            .game
+            .configuration
+            .parse_code(args.next().unwrap_or(""))
+        {
+            Ok(code) => code,
+            Err(_) => return Status::Error,
+        };
+        let crit = match args
+            .next()
+            .and_then(|crit| crit.parse::<u8>().ok())
+            .and_then(|crit| self.game.criterion(crit))
+        {
+            Some(crit) => crit,
+            None => return Status::Error,
+        };
+
+        match crit.verif.rule.explain(&code) {
+            Ok(explanation) => {
+                self.message = Some(explanation);
+                Status::Valid
             }
+            Err(_) => Status::Error,
         }
+    }
 
-        for arg in args {
-            let column_index = self
-                .game
-                .to_column_index(arg.chars().nth(0).unwrap().to_ascii_uppercase());
-            let value =
-                self.striked.len() - 1 - arg.chars().nth(1).unwrap().to_digit(10).unwrap() as usize;
+    fn process_reveal_command(&mut self) -> Status {
+        if let Some(crit) = self.command_line.split(' ').nth(1).map(str::to_string) {
+            return self.reveal_criterion(&crit);
+        }
+        if !self.practice {
+            return Status::Error;
+        }
+
+        self.revealed = true;
+        self.message = Some(format!("The secret code is {}", self.game.code));
+        Status::Valid
+    }
 
-            self.striked[value][column_index as usize].1 ^= true;
+    /// Spends one reveal to show which rule verifies a criteria.
+    fn reveal_criterion(&mut self, crit: &str) -> Status {
+        let Some(crit) = crit
+            .parse::<u8>()
+            .ok()
+            .and_then(|crit| self.game.criterion(crit))
+        else {
+            return Status::Error;
+        };
+        if self.revealed_criterias.contains(&crit.id) {
+            return Status::Error;
+        }
+        if self.reveal_budget == 0 {
+            self.message = Some("No reveals left".to_string());
+            return Status::Error;
         }
 
+        self.reveal_budget -= 1;
+        self.revealed_criterias.push(crit.id);
+        self.message = Some(format!(
+            "Criteria {} is verified by {}",
+            crit.id, crit.verif.rule
+        ));
         Status::Valid
     }
 
@@ -198,7 +805,19 @@ impl GameData {
         Status::Valid
     }
 
+    /// Criteria tested so far, hints aside.
+    pub fn attempts(&self) -> u32 {
+        self.logs.iter().filter(|log| !log.hint).count() as u32
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.game.configuration.is_lost(self.attempts())
+    }
+
     fn process_test_command(&mut self) -> Status {
+        if self.is_lost() {
+            return Status::Error;
+        }
         let mut args = self.command_line.split(' ');
         args.next();
         let code_str = args.next().unwrap_or("");
@@ -206,56 +825,689 @@ impl GameData {
         if code_str.is_empty() || criterias.is_empty() {
             return Status::Error;
         }
-        let code = code_str.to_string().into();
-        if !self.game.is_solution_compatible(&code) {
-            return Status::Error;
-        }
-        for crit in criterias.chars() {
-            if !crit.is_numeric() {
-                return Status::Error;
-            }
-
-            let num = crit.to_digit(10);
+        let code = match self.game.configuration.parse_code(code_str) {
+            Ok(code) => code,
+            Err(_) => return Status::Error,
+        };
+        let crits: Option<Vec<&Criteria>> = criterias
+            .chars()
+            .map(|crit| {
+                crit.to_digit(10)
+                    .and_then(|crit| self.game.criterion(crit as u8))
+            })
+            .collect();
+        let crits = match crits {
+            Some(crits) => crits,
+            None => return Status::Error,
+        };
 
-            match num {
-                Some(n) => {
-                    if n as usize >= self.game.criterias.len() {
-                        return Status::Error;
-                    }
-                }
-                None => return Status::Error,
-            };
+        // Tested pairs are reminded instead of being logged, and cost no attempt
+        let mut already_tested = Vec::new();
+        for crit in crits {
+            // The test going over the allowed attempts ends the game, the rest of the batch with it
+            if self.is_lost() {
+                break;
+            }
+            if let Some(result) = self.tested.get(&(code.clone(), crit.id)) {
+                let result = if *result { "passed" } else { "failed" };
+                already_tested.push(format!("{} {result}", crit.id));
+                continue;
+            }
+            let res = crit.verif.rule.evaluate(code.clone()).unwrap();
+            self.tested.insert((code.clone(), crit.id), res);
+            self.logs.push(GameLog::new(code_str, crit.id, res));
         }
-        for crit in criterias.chars() {
-            let crit_index = crit.to_digit(10).unwrap();
-
-            let res = self.game.criterias[crit_index as usize]
-                .verif
-                .rule
-                .evaluate(code.clone())
-                .unwrap();
-
-            self.logs
-                .push(GameLog::new(code_str, crit_index as u8, res));
+        if !already_tested.is_empty() {
+            self.message = Some(format!(
+                "{code_str} already tested : {}",
+                already_tested.join(", ")
+            ));
+        }
+        if self.is_lost() {
+            self.solution = Some(false);
         }
 
         Status::Valid
     }
 
     fn process_bid_command(&mut self) -> Status {
+        if self.is_lost() {
+            return Status::Error;
+        }
         let mut args = self.command_line.split(' ');
         args.next();
         let solution_str = args.next().unwrap_or("");
         if solution_str.is_empty() {
             return Status::Error;
         }
-        let solution = solution_str.to_string().into();
-        if !self.game.is_solution_compatible(&solution) {
-            return Status::Error;
-        }
+        let solution = match self.game.configuration.parse_code(solution_str) {
+            Ok(solution) => solution,
+            Err(_) => return Status::Error,
+        };
 
         self.solution = Some(solution == self.game.code);
 
         Status::Valid
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClickAction, CriteriaLayout, Focus, GameData, GameLog, Status, StrikeOrientation, Toggle,
+        HISTORY_SIZE,
+    };
+    use crate::hint::Deductions;
+    use crate::input::InputEvent;
+    use crossterm::event::{KeyCode, KeyEvent, MouseButton};
+    use enigmind_lib::code::Code;
+    use enigmind_lib::setup::generate_game_seeded;
+    use std::{env, fs, path::PathBuf};
+    use tui::layout::Rect;
+
+    fn game_data() -> GameData {
+        GameData::new(generate_game_seeded(5, 3, 10, 1).unwrap())
+    }
+
+    /// Save file unique to a test and to this process, removed once the test is over.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            Self(env::temp_dir().join(format!("enigmind_tui_{name}_{}.json", std::process::id())))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut gd = game_data();
+        gd.logs.push(GameLog::new("123", 0, true));
+        gd.logs.push(GameLog::new("240", 1, false));
+        gd.logs.push(GameLog::new("401", 0, false));
+        gd.striked[1][2].1 = true;
+        gd.criterias_state[0][0] = false;
+
+        let file = TempFile::new("round_trip");
+        gd.save(&file.0).unwrap();
+        let loaded = GameData::load(&file.0).unwrap();
+
+        assert_eq!(loaded.game.code, gd.game.code);
+        assert_eq!(loaded.logs.len(), 3);
+        assert_eq!(loaded.logs[1].code, "240");
+        assert_eq!(loaded.logs[1].crit_index, 1);
+        assert!(!loaded.logs[1].result);
+        assert_eq!(loaded.striked, gd.striked);
+        assert_eq!(loaded.criterias_state, gd.criterias_state);
+    }
+
+    #[test]
+    fn test_load_rejects_mismatching_grid() {
+        let mut gd = game_data();
+        gd.striked.pop();
+
+        let file = TempFile::new("mismatch");
+        gd.save(&file.0).unwrap();
+
+        assert!(GameData::load(&file.0).is_err());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut gd = game_data();
+        let initial = gd.striked.clone();
+
+        gd.process_click_action(ClickAction::ToggleStrike(0, 1));
+        let after_first = gd.striked.clone();
+        gd.process_click_action(ClickAction::ToggleCriteriaRule(0, 0));
+        gd.command_line = "s A2 B3".to_string();
+        gd.process_commands();
+
+        assert!(gd.undo() == Status::Valid);
+        assert!(gd.undo() == Status::Valid);
+        assert_eq!(gd.striked, after_first);
+        assert!(gd.criterias_state[0][0]);
+
+        assert!(gd.redo() == Status::Valid);
+        assert!(!gd.criterias_state[0][0]);
+
+        assert!(gd.undo() == Status::Valid);
+        assert!(gd.undo() == Status::Valid);
+        assert_eq!(gd.striked, initial);
+        assert!(gd.undo() == Status::Error);
+    }
+
+    #[test]
+    fn test_criteria_layout_grid() {
+        assert_eq!(CriteriaLayout::Auto.grid(1), (1, 1));
+        assert_eq!(CriteriaLayout::Auto.grid(4), (2, 2));
+        assert_eq!(CriteriaLayout::Auto.grid(5), (3, 2));
+        assert_eq!(CriteriaLayout::Auto.grid(7), (3, 3));
+
+        assert_eq!(CriteriaLayout::Columns(4).grid(7), (4, 2));
+        assert_eq!(CriteriaLayout::Columns(4).grid(8), (4, 2));
+        assert_eq!(CriteriaLayout::Columns(4).grid(9), (4, 3));
+        assert_eq!(CriteriaLayout::Columns(10).grid(3), (3, 1));
+
+        assert_eq!(CriteriaLayout::Rows(2).grid(5), (3, 2));
+        assert_eq!(CriteriaLayout::Rows(1).grid(6), (6, 1));
+        assert_eq!(CriteriaLayout::Rows(8).grid(3), (1, 3));
+
+        let mut gd = game_data();
+        gd.command_line = "layout cols 4".to_string();
+        gd.process_commands();
+        assert_eq!(gd.criteria_layout, CriteriaLayout::Columns(4));
+
+        gd.command_line = "layout rows 0".to_string();
+        gd.process_commands();
+        assert!(gd.command_status == Status::Error);
+        assert_eq!(gd.criteria_layout, CriteriaLayout::Columns(4));
+    }
+
+    #[test]
+    fn test_focus_movement() {
+        let mut gd = game_data();
+        let last_crit = gd.game.criterias.len() - 1;
+        let press = |gd: &mut GameData, code| gd.process_key(KeyEvent::from(code));
+
+        press(&mut gd, KeyCode::Tab);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::CriteriaRule(0, 0)));
+
+        press(&mut gd, KeyCode::Left);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::CriteriaRule(last_crit, 0)));
+        press(&mut gd, KeyCode::Right);
+        press(&mut gd, KeyCode::Up);
+        let last_rule = gd.criterias_state[0].len() - 1;
+        assert_eq!(gd.focus, Focus::Cell(Toggle::CriteriaRule(0, last_rule)));
+        press(&mut gd, KeyCode::Down);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::CriteriaRule(0, 0)));
+
+        press(&mut gd, KeyCode::Char(' '));
+        assert!(!gd.criterias_state[0][0]);
+        assert!(gd.command_line.is_empty());
+
+        press(&mut gd, KeyCode::Tab);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::Strike(0, 0)));
+        press(&mut gd, KeyCode::Up);
+        press(&mut gd, KeyCode::Left);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::Strike(4, 2)));
+        press(&mut gd, KeyCode::Right);
+        assert_eq!(gd.focus, Focus::Cell(Toggle::Strike(4, 0)));
+        press(&mut gd, KeyCode::Char(' '));
+        assert!(gd.striked[4][0].1);
+
+        press(&mut gd, KeyCode::Tab);
+        assert_eq!(gd.focus, Focus::CommandLine);
+        press(&mut gd, KeyCode::Char(' '));
+        assert_eq!(gd.command_line, " ");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut gd = game_data();
+        for command in ["t 123 0", "s A1", "unknown"] {
+            gd.command_line = command.to_string();
+            gd.process_commands();
+        }
+        gd.command_line = "t 4".to_string();
+
+        let press = |gd: &mut GameData, code| gd.process_key(KeyEvent::from(code));
+        press(&mut gd, KeyCode::Up);
+        assert_eq!(gd.command_line, "unknown");
+        press(&mut gd, KeyCode::Up);
+        assert_eq!(gd.command_line, "s A1");
+        press(&mut gd, KeyCode::Up);
+        assert_eq!(gd.command_line, "t 123 0");
+        press(&mut gd, KeyCode::Up);
+        assert_eq!(gd.command_line, "t 123 0");
+
+        press(&mut gd, KeyCode::Down);
+        assert_eq!(gd.command_line, "s A1");
+        press(&mut gd, KeyCode::Down);
+        press(&mut gd, KeyCode::Down);
+        assert_eq!(gd.command_line, "t 4");
+        press(&mut gd, KeyCode::Down);
+        assert_eq!(gd.command_line, "t 4");
+
+        for i in 0..=HISTORY_SIZE {
+            gd.command_line = format!("unknown {i}");
+            gd.process_commands();
+        }
+        assert_eq!(gd.history.len(), HISTORY_SIZE);
+        assert_eq!(gd.history[0], "unknown 1");
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut gd = game_data();
+        let press_tab = |gd: &mut GameData| gd.process_key(KeyEvent::from(KeyCode::Tab));
+
+        gd.command_line = "t".to_string();
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "t ");
+
+        gd.command_line = "u".to_string();
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "undo ");
+
+        gd.command_line = "l".to_string();
+        assert_eq!(gd.completions(), vec!["layout", "load"]);
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "layout");
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "load");
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "layout");
+
+        gd.command_line = "layout c".to_string();
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "layout cols");
+
+        gd.command_line = "s A1 ".to_string();
+        assert_eq!(gd.completions(), vec!["s A1 A", "s A1 B", "s A1 C"]);
+
+        gd.command_line = "t 123 0".to_string();
+        let crits = gd.completions();
+        assert_eq!(crits.len(), gd.game.criterias.len() - 1);
+        assert_eq!(crits[0], "t 123 01");
+
+        // Criteria are told apart by id, not by their text
+        gd.game.criterias = gd
+            .game
+            .criterias
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, mut crit)| {
+                crit.id = [1, 11, 0].get(index).copied().unwrap_or(20 + index as u8);
+                crit
+            })
+            .collect();
+        gd.command_line = "t 123 1".to_string();
+        assert_eq!(gd.completions(), vec!["t 123 10"]);
+
+        gd.command_line = "xyz".to_string();
+        press_tab(&mut gd);
+        assert_eq!(gd.command_line, "xyz");
+        assert_eq!(gd.focus, Focus::CommandLine);
+    }
+
+    #[test]
+    fn test_click_event() {
+        let mut gd = game_data();
+        gd.click_areas
+            .push((Rect::new(10, 5, 1, 1), ClickAction::ToggleStrike(2, 1)));
+
+        gd.handle_event(InputEvent::Click(MouseButton::Right, 10, 5));
+        assert!(!gd.striked[1][2].1);
+
+        gd.handle_event(InputEvent::Click(MouseButton::Left, 11, 5));
+        assert!(!gd.striked[1][2].1);
+
+        gd.handle_event(InputEvent::Click(MouseButton::Left, 10, 5));
+        assert!(gd.striked[1][2].1);
+    }
+
+    #[test]
+    fn test_strike_orientation() {
+        for orientation in [StrikeOrientation::Descending, StrikeOrientation::Ascending] {
+            for value in 0..5 {
+                assert_eq!(orientation.value(orientation.line(value, 5), 5), value);
+            }
+        }
+        assert_eq!(StrikeOrientation::Descending.line(0, 5), 4);
+        assert_eq!(StrikeOrientation::Descending.line(4, 5), 0);
+        assert_eq!(StrikeOrientation::Ascending.line(0, 5), 0);
+        assert_eq!(StrikeOrientation::Ascending.line(4, 5), 4);
+
+        // The s command strikes the same cell whatever the orientation
+        let mut gd = game_data();
+        gd.command_line = "digits asc".to_string();
+        gd.process_commands();
+        assert_eq!(gd.strike_orientation, StrikeOrientation::Ascending);
+        gd.command_line = "s B0 C4".to_string();
+        gd.process_commands();
+        assert!(gd.striked[gd.strike_line(0)][1].1);
+        assert!(gd.striked[4][1].1);
+        assert!(gd.striked[0][2].1);
+        assert_eq!(gd.striked[gd.strike_line(4)][2].0, '4');
+
+        // Moving down the displayed grid goes up the digits stored highest first
+        gd.focus = Focus::Cell(Toggle::Strike(gd.strike_line(0), 0));
+        gd.process_key(KeyEvent::from(KeyCode::Down));
+        assert_eq!(gd.focus, Focus::Cell(Toggle::Strike(gd.strike_line(1), 0)));
+    }
+
+    #[test]
+    fn test_test_command() {
+        let mut gd = game_data();
+        let last_crit = gd.game.criteria_count() - 1;
+
+        gd.command_line = format!("t 123 0{last_crit}");
+        gd.process_commands();
+        assert!(gd.command_status == Status::Valid);
+        assert_eq!(gd.logs.len(), 2);
+        assert_eq!(gd.logs[1].crit_index, last_crit);
+
+        for command in ["t 123 09", "t 123 0x", "t 123", "t 999 0"] {
+            gd.command_line = command.to_string();
+            gd.process_commands();
+            assert!(gd.command_status == Status::Error);
+            assert_eq!(gd.logs.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_repeated_test_is_not_logged() {
+        let mut gd = game_data();
+        let expected = gd
+            .game
+            .criterion(0)
+            .unwrap()
+            .verif
+            .rule
+            .evaluate(Code::new(vec![1, 2, 3]))
+            .unwrap();
+
+        gd.command_line = "t 123 0".to_string();
+        gd.process_commands();
+        assert!(gd.message.is_none());
+        gd.command_line = "t 123 01".to_string();
+        gd.process_commands();
+
+        assert!(gd.command_status == Status::Valid);
+        assert_eq!(gd.logs.len(), 2);
+        assert_eq!(gd.attempts(), 2);
+        let result = if expected { "passed" } else { "failed" };
+        assert_eq!(
+            gd.message.as_deref(),
+            Some(format!("123 already tested : 0 {result}").as_str())
+        );
+
+        // The reminder only lasts until the next command
+        gd.command_line = "t 124 0".to_string();
+        gd.process_commands();
+        assert!(gd.message.is_none());
+
+        let file = TempFile::new("repeated");
+        gd.save(&file.0).unwrap();
+        let mut loaded = GameData::load(&file.0).unwrap();
+        loaded.command_line = "t 123 1".to_string();
+        loaded.process_commands();
+        assert_eq!(loaded.logs.len(), 3);
+        let result = if loaded.logs[1].result {
+            "passed"
+        } else {
+            "failed"
+        };
+        assert_eq!(
+            loaded.message,
+            Some(format!("123 already tested : 1 {result}"))
+        );
+    }
+
+    #[test]
+    fn test_lost_after_max_attempts() {
+        let mut gd = game_data();
+        gd.game.configuration.max_attempts = Some(3);
+
+        gd.command_line = "hint".to_string();
+        gd.process_commands();
+        // Distinct codes, repeated tests costing no attempt
+        let test = |gd: &mut GameData, code: &str| {
+            gd.command_line = format!("t {code} 0");
+            gd.process_commands();
+        };
+        for code in ["120", "121", "122"] {
+            test(&mut gd, code);
+            assert!(gd.command_status == Status::Valid);
+            assert!(!gd.is_lost());
+        }
+        assert!(gd.solution.is_none());
+
+        test(&mut gd, "123");
+        assert!(gd.command_status == Status::Valid);
+        assert!(gd.is_lost());
+        assert_eq!(gd.solution, Some(false));
+
+        test(&mut gd, "124");
+        assert!(gd.command_status == Status::Error);
+        gd.command_line = format!("b {}", gd.game.code);
+        gd.process_commands();
+        assert!(gd.command_status == Status::Error);
+        assert_eq!(gd.attempts(), 4);
+
+        // A batch stops at the test going over the allowed attempts
+        let mut gd = game_data();
+        gd.game.configuration.max_attempts = Some(3);
+        gd.command_line = "t 120 012".to_string();
+        gd.process_commands();
+        assert!(!gd.is_lost());
+        gd.command_line = "t 121 012".to_string();
+        gd.process_commands();
+        assert!(gd.is_lost());
+        assert_eq!(gd.solution, Some(false));
+        assert_eq!(gd.attempts(), 4);
+    }
+
+    #[test]
+    fn test_explain_needs_practice() {
+        let mut gd = game_data();
+        gd.command_line = "explain 123 0".to_string();
+        gd.process_commands();
+        assert!(gd.command_status == Status::Error);
+        assert!(gd.message.is_none());
+
+        gd.practice = true;
+        gd.process_commands();
+        assert!(gd.command_status == Status::Valid);
+        let expected = gd.game.criterias[0]
+            .verif
+            .rule
+            .explain(&Code::new(vec![1, 2, 3]))
+            .unwrap();
+        assert_eq!(gd.message, Some(expected));
+
+        gd.command_line = "explain 123 99".to_string();
+        gd.process_commands();
+        assert!(gd.command_status == Status::Error);
+        assert!(gd.message.is_none());
+    }
+
+    #[test]
+    fn test_reveal_needs_practice() {
+        let mut gd = game_data();
+        gd.command_line = "reveal".to_string();
+        gd.process_commands();
+        assert!(gd.command_status == Status::Error);
+        assert!(!gd.revealed);
+        assert!(gd.message.is_none());
+
+        gd.practice = true;
+        gd.process_commands();
+        assert!(gd.command_status == Status::Valid);
+        assert!(gd.revealed);
+        assert!(gd.message.unwrap().contains(&gd.game.code.to_string()));
+    }
+
+    #[test]
+    fn test_reveal_budget() {
+        let mut gd = game_data();
+        gd.reveal_budget = 2;
+        let reveal = |gd: &mut GameData, crit: &str| {
+            gd.command_line = format!("reveal {crit}");
+            gd.process_commands();
+            gd.command_status == Status::Valid
+        };
+
+        assert!(reveal(&mut gd, "0"));
+        assert!(gd
+            .message
+            .as_ref()
+            .unwrap()
+            .contains(&gd.game.criterias[0].verif.rule.to_string()));
+        // Already revealed, or not a criteria: nothing is spent
+        assert!(!reveal(&mut gd, "0"));
+        assert!(!reveal(&mut gd, "99"));
+        assert_eq!(gd.reveal_budget, 1);
+
+        assert!(reveal(&mut gd, "1"));
+        assert_eq!(gd.reveal_budget, 0);
+        assert_eq!(gd.revealed_criterias, vec![0, 1]);
+
+        assert!(!reveal(&mut gd, "2"));
+        assert_eq!(gd.message.as_deref(), Some("No reveals left"));
+        assert_eq!(gd.revealed_criterias, vec![0, 1]);
+        assert!(!gd.revealed);
+    }
+
+    #[test]
+    fn test_base_9_strike_grid() {
+        let mut gd = GameData::new(generate_game_seeded(9, 2, 0, 1).unwrap());
+        assert_eq!(gd.striked.len(), 9);
+        assert_eq!(gd.striked[0][0].0, '8');
+        assert_eq!(gd.striked[8][1].0, '0');
+
+        gd.command_line = "s B8".to_string();
+        gd.process_commands();
+        assert!(gd.striked[0][1].1);
+    }
+
+    #[test]
+    fn test_check_consistency() {
+        let mut gd = game_data();
+        assert!(gd.check_consistency().is_empty());
+
+        // Keeping only the verifiers leaves the secret code as the only candidate
+        for (crit_index, crit) in gd.game.criterias.iter().enumerate() {
+            for (rule_index, rule) in crit.rules.iter().enumerate() {
+                gd.criterias_state[crit_index][rule_index] = *rule == crit.verif.rule;
+            }
+        }
+        assert!(gd.check_consistency().is_empty());
+
+        let value = gd.game.code.0[0];
+        let line = gd.strike_line(value);
+        gd.striked[line][0].1 = true;
+        let warnings = gd.check_consistency();
+        assert!(warnings.contains(&format!(
+            "A{value} is struck but every code matching the tests has it"
+        )));
+        assert!(warnings.contains(&"No code left matches the strikes and the tests".to_string()));
+
+        gd.striked[line][0].1 = false;
+        // Only a rule contradicted by a test result is left for criteria 0
+        let crit = &gd.game.criterias[0];
+        let (wrong, code) = crit
+            .rules
+            .iter()
+            .enumerate()
+            .flat_map(|(index, rule)| {
+                gd.game
+                    .configuration
+                    .codes()
+                    .map(move |code| (index, rule, code))
+            })
+            .find(|(_, rule, code)| {
+                rule.evaluate(code.clone()).unwrap()
+                    != crit.verif.rule.evaluate(code.clone()).unwrap()
+            })
+            .map(|(index, _, code)| (index, code))
+            .unwrap();
+        let result = crit.verif.rule.evaluate(code.clone()).unwrap();
+        gd.logs.push(GameLog::new(&code.to_string(), 0, result));
+        gd.criterias_state[0] = vec![false; gd.criterias_state[0].len()];
+        gd.criterias_state[0][wrong] = true;
+        assert!(gd
+            .check_consistency()
+            .contains(&"Criteria 0 : no enabled rule matches the tests".to_string()));
+    }
+
+    #[test]
+    fn test_criteria_ids_out_of_order() {
+        let mut game = generate_game_seeded(5, 3, 10, 1).unwrap();
+        game.criterias = game.criterias.iter().rev().cloned().collect();
+        let mut gd = GameData::new(game);
+        let crit = gd.game.criterias[0].clone();
+        assert_ne!(crit.id, 0);
+
+        // A test result only rules out the rules of the criteria it was logged for
+        let (wrong, code) = crit
+            .rules
+            .iter()
+            .enumerate()
+            .flat_map(|(index, rule)| {
+                gd.game
+                    .configuration
+                    .codes()
+                    .map(move |code| (index, rule, code))
+            })
+            .find(|(_, rule, code)| {
+                rule.evaluate(code.clone()).unwrap()
+                    != crit.verif.rule.evaluate(code.clone()).unwrap()
+            })
+            .map(|(index, _, code)| (index, code))
+            .unwrap();
+        gd.command_line = format!("t {code} {}", crit.id);
+        gd.process_commands();
+        gd.criterias_state[0] = vec![false; gd.criterias_state[0].len()];
+        gd.criterias_state[0][wrong] = true;
+        assert!(gd.check_consistency().contains(&format!(
+            "Criteria {} : no enabled rule matches the tests",
+            crit.id
+        )));
+
+        // Hints name the criterias by id
+        let mut gd = GameData::new(gd.game.clone());
+        gd.command_line = "hint".to_string();
+        gd.process_commands();
+        assert!(gd.logs.iter().any(|log| log.hint));
+        let deductions = Deductions::new(&gd);
+        let configuration = &gd.game.configuration;
+        for log in gd.logs.iter().filter(|log| log.hint) {
+            let code = configuration.parse_code(&log.code).unwrap();
+            let position = gd
+                .game
+                .criterias
+                .iter()
+                .position(|crit| crit.id == log.crit_index)
+                .unwrap();
+            let (right, wrong) =
+                deductions.outcomes(code.get_shift(configuration) as usize, position);
+            assert!(right > 0 && wrong > 0);
+        }
+    }
+
+    #[test]
+    fn test_remaining_candidates() {
+        let mut gd = game_data();
+        let mut remaining = gd.remaining_candidates().unwrap();
+        assert!(remaining > 1 && remaining <= gd.game.configuration.codes().count());
+
+        // Neither hints nor strikes are results the player learnt
+        gd.logs.push(GameLog::hint("000", 0));
+        gd.command_line = "s A1 B2 C3".to_string();
+        gd.process_commands();
+        assert_eq!(gd.remaining_candidates().unwrap(), remaining);
+
+        for (code, crit) in gd.game.optimal_solution_path() {
+            gd.command_line = format!("t {code} {crit}");
+            gd.process_commands();
+
+            let now = gd.remaining_candidates().unwrap();
+            assert!(now < remaining);
+            remaining = now;
+        }
+        assert_eq!(remaining, 1);
+
+        // A log the game can't account for is reported, rather than counted as no code left
+        gd.logs
+            .push(GameLog::new("000", gd.game.criteria_count(), true));
+        assert!(gd.remaining_candidates().is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use enigmind_lib::code::Code;
+
+use crate::game_data::GameData;
+
+/// What the player can deduce from the criteria rules still enabled, the test
+/// results and the strike grid, without knowing the secret code.
+pub struct Deductions {
+    ids: Vec<u8>,
+    codes: Vec<Code>,
+    allowed: Vec<bool>,
+    rule_masks: Vec<Vec<Vec<bool>>>,
+    accepted: Vec<Vec<bool>>,
+}
+
+impl Deductions {
+    pub fn new(gd: &GameData) -> Self {
+        let configuration = &gd.game.configuration;
+        let codes: Vec<Code> = configuration.codes().collect();
+
+        let allowed = codes
+            .iter()
+            .map(|code| {
+                code.0
+                    .iter()
+                    .enumerate()
+                    .all(|(x, value)| !gd.striked[gd.strike_line(*value)][x].1)
+            })
+            .collect();
+
+        let mut rule_masks = Vec::new();
+        for (crit_index, crit) in gd.game.criterias.iter().enumerate() {
+            let mut masks = Vec::new();
+            for (rule_index, rule) in crit.rules.iter().enumerate() {
+                let mask: Vec<bool> = codes
+                    .iter()
+                    .map(|code| rule.evaluate(code.clone()).unwrap_or(false))
+                    .collect();
+
+                let contradicted = gd
+                    .logs
+                    .iter()
+                    .filter(|log| !log.hint && log.crit_index == crit.id)
+                    .any(|log| {
+                        configuration
+                            .parse_code(&log.code)
+                            .map(|code| mask[code.get_shift(configuration) as usize] != log.result)
+                            .unwrap_or(false)
+                    });
+
+                if gd.criterias_state[crit_index][rule_index] && !contradicted {
+                    masks.push(mask);
+                }
+            }
+            rule_masks.push(masks);
+        }
+
+        let accepted = rule_masks
+            .iter()
+            .map(|masks| {
+                (0..codes.len())
+                    .map(|i| masks.iter().any(|mask| mask[i]))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            ids: gd.game.criterias.iter().map(|crit| crit.id).collect(),
+            codes,
+            allowed,
+            rule_masks,
+            accepted,
+        }
+    }
+
+    fn count(&self, skipped_crit: Option<usize>, extra: &[bool]) -> usize {
+        (0..self.codes.len())
+            .filter(|&i| {
+                self.allowed[i]
+                    && extra[i]
+                    && self
+                        .accepted
+                        .iter()
+                        .enumerate()
+                        .all(|(crit, accepted)| Some(crit) == skipped_crit || accepted[i])
+            })
+            .count()
+    }
+
+    pub fn candidate_count(&self) -> usize {
+        self.count(None, &vec![true; self.codes.len()])
+    }
+
+    /// Ids of the criterias whose enabled rules all contradict the test results.
+    pub fn unsatisfiable_criterias(&self) -> Vec<u8> {
+        (0..self.rule_masks.len())
+            .filter(|&crit| self.rule_masks[crit].is_empty())
+            .map(|crit| self.ids[crit])
+            .collect()
+    }
+
+    /// Column and digit shared by every code matching the tests, whatever the strikes.
+    pub fn required_digits(&self) -> Vec<(usize, u8)> {
+        let mut matching = (0..self.codes.len())
+            .filter(|&i| self.accepted.iter().all(|accepted| accepted[i]))
+            .map(|i| &self.codes[i]);
+
+        let first = match matching.next() {
+            Some(code) => code.0.clone(),
+            None => return Vec::new(),
+        };
+        let mut required: Vec<Option<u8>> = first.into_iter().map(Some).collect();
+        for code in matching {
+            for (digit, value) in required.iter_mut().zip(code.0.iter()) {
+                if *digit != Some(*value) {
+                    *digit = None;
+                }
+            }
+        }
+
+        required
+            .into_iter()
+            .enumerate()
+            .filter_map(|(x, digit)| digit.map(|d| (x, d)))
+            .collect()
+    }
+
+    /// Number of candidates remaining if testing the given code against the criteria at
+    /// the given position turns out right or wrong.
+    pub fn outcomes(&self, code_index: usize, crit: usize) -> (usize, usize) {
+        let mut right = vec![false; self.codes.len()];
+        let mut wrong = vec![false; self.codes.len()];
+
+        for mask in &self.rule_masks[crit] {
+            let outcome = if mask[code_index] {
+                &mut right
+            } else {
+                &mut wrong
+            };
+            for (i, accepted) in mask.iter().enumerate() {
+                outcome[i] |= accepted;
+            }
+        }
+
+        (
+            self.count(Some(crit), &right),
+            self.count(Some(crit), &wrong),
+        )
+    }
+
+    /// Code and the ids of up to 3 criteria whose test leaves the fewest candidates in the worst case.
+    pub fn suggest(&self) -> Option<(Code, Vec<u8>)> {
+        let total = self.candidate_count();
+
+        let mut best: Option<(usize, Vec<(usize, usize)>)> = None;
+        for code_index in 0..self.codes.len() {
+            let mut scores: Vec<(usize, usize)> = (0..self.rule_masks.len())
+                .map(|crit| {
+                    let (right, wrong) = self.outcomes(code_index, crit);
+                    (right.max(wrong), crit)
+                })
+                .filter(|(worst, _)| *worst < total)
+                .collect();
+            scores.sort();
+
+            let is_better = match (&best, scores.first()) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some((_, best_scores)), Some(score)) => *score < best_scores[0],
+            };
+            if is_better {
+                best = Some((code_index, scores));
+            }
+        }
+
+        best.map(|(code_index, scores)| {
+            (
+                self.codes[code_index].clone(),
+                scores
+                    .iter()
+                    .take(3)
+                    .map(|(_, crit)| self.ids[*crit])
+                    .collect(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deductions;
+    use crate::game_data::GameData;
+    use enigmind_lib::setup::generate_game_seeded;
+
+    #[test]
+    fn test_suggest_splits_most_evenly() {
+        let gd = GameData::new(generate_game_seeded(5, 3, 10, 3).unwrap());
+        let deductions = Deductions::new(&gd);
+
+        let (code, crits) = deductions.suggest().unwrap();
+        assert!(!crits.is_empty() && crits.len() <= 3);
+
+        let configuration = &gd.game.configuration;
+        let position = gd
+            .game
+            .criterias
+            .iter()
+            .position(|crit| crit.id == crits[0])
+            .unwrap();
+        let (right, wrong) = deductions.outcomes(code.get_shift(configuration) as usize, position);
+        assert!(right > 0 && wrong > 0);
+
+        for code_index in 0..configuration.solution_count() as usize {
+            for crit in 0..gd.game.criterias.len() {
+                let (other_right, other_wrong) = deductions.outcomes(code_index, crit);
+                assert!(other_right.max(other_wrong) >= right.max(wrong));
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use std::{
-    sync::mpsc::{channel, Receiver, RecvError, Sender},
-    thread,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
@@ -9,7 +14,7 @@ use crossterm::event::{self, KeyEvent, MouseButton};
 pub enum InputEvent {
     /// An input event occurred.
     Input(KeyEvent),
-    ///
+    /// A mouse click occurred.
     Click(MouseButton, u16, u16),
     /// An tick event occurred.
     Tick,
@@ -19,40 +24,69 @@ pub struct Events {
     rx: Receiver<InputEvent>,
     // Need to be kept around to prevent disposing the sender side.
     _tx: Sender<InputEvent>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Terminal event read within `tick_rate`, if any.
+fn terminal_event(tick_rate: Duration) -> io::Result<Option<event::Event>> {
+    match event::poll(tick_rate)? {
+        true => event::read().map(Some),
+        false => Ok(None),
+    }
 }
 
 impl Events {
     pub fn new(tick_rate: Duration) -> Events {
+        Events::with_source(tick_rate, terminal_event)
+    }
+
+    /// Listens to the events `source` reads within a tick, until it fails or is dropped.
+    fn with_source(
+        tick_rate: Duration,
+        mut source: impl FnMut(Duration) -> io::Result<Option<event::Event>> + Send + 'static,
+    ) -> Events {
         let (tx, rx) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
 
         let event_tx = tx.clone(); // the thread::spawn own event_tx
+        let thread_stop = stop.clone();
 
-        thread::spawn(move || {
-            loop {
+        let handle = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
                 // poll for tick rate duration, if no event, sent tick event.
-                if crossterm::event::poll(tick_rate).unwrap() {
-                    match event::read().unwrap() {
-                        event::Event::Key(key) => event_tx.send(InputEvent::Input(key)).unwrap(),
-                        event::Event::Mouse(mouse_event) => {
-                            if let event::MouseEventKind::Down(MouseButton::Left) = mouse_event.kind
-                            {
-                                event_tx
-                                    .send(InputEvent::Click(
-                                        MouseButton::Left,
-                                        mouse_event.column,
-                                        mouse_event.row,
-                                    ))
-                                    .unwrap();
-                            }
-                        }
-                        _ => (),
-                    };
+                // Without a terminal to read from there is nothing left to listen to.
+                let event = match source(tick_rate) {
+                    Ok(Some(event::Event::Key(key))) => Some(InputEvent::Input(key)),
+                    Ok(Some(event::Event::Mouse(mouse_event))) => match mouse_event.kind {
+                        event::MouseEventKind::Down(button) => Some(InputEvent::Click(
+                            button,
+                            mouse_event.column,
+                            mouse_event.row,
+                        )),
+                        _ => None,
+                    },
+                    Ok(_) => None,
+                    Err(_) => break,
+                };
+
+                if let Some(event) = event {
+                    if event_tx.send(event).is_err() {
+                        break;
+                    }
+                }
+                if event_tx.send(InputEvent::Tick).is_err() {
+                    break;
                 }
-                event_tx.send(InputEvent::Tick).unwrap();
             }
         });
 
-        Events { rx, _tx: tx }
+        Events {
+            rx,
+            _tx: tx,
+            stop,
+            handle: Some(handle),
+        }
     }
 
     /// Attempts to read an event.
@@ -61,3 +95,37 @@ impl Events {
         self.rx.recv()
     }
 }
+
+impl Drop for Events {
+    /// Stops the input thread, which notices it within one tick.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Events, InputEvent};
+    use std::{sync::mpsc::channel, thread, time::Duration};
+
+    #[test]
+    fn test_drop_stops_input_thread() {
+        let (done_tx, done_rx) = channel();
+
+        thread::spawn(move || {
+            // A source that never fails, so only the drop can stop the thread
+            let events = Events::with_source(Duration::from_millis(10), |tick_rate| {
+                thread::sleep(tick_rate);
+                Ok(None)
+            });
+            assert!(matches!(events.next(), Ok(InputEvent::Tick)));
+            drop(events);
+            done_tx.send(()).unwrap();
+        });
+
+        assert!(done_rx.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
@@ -4,6 +4,7 @@
 
 mod draw;
 mod game_data;
+mod hint;
 mod input;
 
 use std::{
@@ -13,7 +14,7 @@ use std::{
 
 use anyhow::Result;
 use crossterm::{event::EnableMouseCapture, ExecutableCommand};
-use enigmind_lib::setup::generate_game;
+use enigmind_lib::setup::{generate_game, GenerationOptions};
 use game_data::GameData;
 use input::Events;
 use tui::{backend::CrosstermBackend, Terminal};
@@ -42,6 +43,9 @@ pub fn start_ui(gd: &mut GameData) -> Result<()> {
         gd.handle_events(&events);
     }
 
+    // Stop listening to the terminal before handing it back
+    drop(events);
+
     // Restore the terminal and close application
     terminal.clear()?;
     terminal.show_cursor()?;
@@ -51,9 +55,30 @@ pub fn start_ui(gd: &mut GameData) -> Result<()> {
 }
 
 fn main() -> Result<()> {
-    let game = generate_game(5, 3, 10).unwrap();
+    let args: Vec<String> = std::env::args().collect();
+    let max_attempts = args
+        .iter()
+        .position(|arg| arg == "--max-attempts")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|max| max.parse::<u32>().ok());
+
+    let game = generate_game(GenerationOptions {
+        base: 5,
+        column_count: 3,
+        min_difficulty: 10,
+        max_attempts,
+        ..Default::default()
+    })
+    .unwrap();
 
     let mut gd = GameData::new(game);
+    gd.practice = args.iter().any(|arg| arg == "--practice");
+    gd.reveal_budget = args
+        .iter()
+        .position(|arg| arg == "--reveals")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|reveals| reveals.parse::<u8>().ok())
+        .unwrap_or(0);
 
     start_ui(&mut gd)?;
     Ok(())
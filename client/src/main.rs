@@ -1,11 +1,20 @@
+mod mastermind;
+mod score;
+
 use std::{
     io::{self, Write},
     str::FromStr,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
-use enigmind_lib::{code::Code, setup::Game};
+use enigmind_lib::{
+    code::Code,
+    rule::Rule,
+    setup::{GameConfiguration, PublicGame, MAX_BASE},
+};
+use score::compute_score;
+use serde::Deserialize;
 
 use tokio::{
     select,
@@ -57,26 +66,53 @@ pub fn read_string_from_terminal(text: String) -> String {
     input
 }
 
-pub fn read_valid_code_from_terminal(text: String, game: &Game) -> Code {
+pub fn read_valid_code_from_terminal(text: String, configuration: &GameConfiguration) -> Code {
     loop {
-        let solution = read_string_from_terminal(text.clone()).into();
-        if !game.is_solution_compatible(&solution) {
-            println!(
+        let input = read_string_from_terminal(text.clone());
+        match configuration.parse_code(input.trim()) {
+            Ok(solution) => return solution,
+            Err(_) => println!(
                 "Your solution is invalid ({} digits between 0 and {})",
-                game.configuration.column_count,
-                game.configuration.base - 1
-            );
-        } else {
-            return solution;
+                configuration.column_count,
+                configuration.base - 1
+            ),
         }
     }
 }
 
+const DEFAULT_SERVER: &str = "http://localhost:3000";
+
+fn server_url() -> String {
+    std::env::var("ENIGMIND_SERVER")
+        .map(|server| server.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| DEFAULT_SERVER.to_string())
+}
+
+fn generate_url(
+    server: &str,
+    base: u8,
+    column_count: u8,
+    difficulty: u8,
+    max_attempts: Option<u32>,
+    reveal_budget: u8,
+) -> String {
+    let mut url = format!(
+        "{server}/generate?base={base}&column_count={column_count}&difficulty={difficulty}"
+    );
+    if let Some(max_attempts) = max_attempts {
+        url.push_str(&format!("&max_attempts={max_attempts}"));
+    }
+    if reveal_budget > 0 {
+        url.push_str(&format!("&reveal_budget={reveal_budget}"));
+    }
+    url
+}
+
 async fn server_availability_check() -> Result<bool> {
     print!("Checking server availability... ");
     io::stdout().flush().unwrap();
 
-    let request_url = "http://localhost:3000/ping".to_string();
+    let request_url = format!("{}/ping", server_url());
     let response = reqwest::get(&request_url).await?;
 
     let s: String = response.json().await?;
@@ -92,9 +128,113 @@ async fn print_dot_each_second() {
     }
 }
 
-async fn get_game_data(base: u8, column_count: u8) -> Result<Game, anyhow::Error> {
-    let request_url =
-        format!("http://localhost:3000/generate?base={base}&column_count={column_count}");
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GeneratedGame {
+    session: String,
+    game: PublicGame,
+}
+
+async fn get_game_data(
+    base: u8,
+    column_count: u8,
+    difficulty: u8,
+    max_attempts: Option<u32>,
+    reveal_budget: u8,
+) -> Result<GeneratedGame, anyhow::Error> {
+    let request_url = generate_url(
+        &server_url(),
+        base,
+        column_count,
+        difficulty,
+        max_attempts,
+        reveal_budget,
+    );
+
+    let response = reqwest::get(&request_url).await?;
+
+    if !response.status().is_success() {
+        let error: ApiError = response.json().await?;
+        return Err(anyhow::anyhow!(error.message));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|reqwest_err| reqwest_err.into())
+}
+
+#[derive(Deserialize)]
+struct TestResults {
+    results: Vec<(u8, bool)>,
+    lost: Option<Code>,
+}
+
+/// Result of a code against a criteria, only the server knowing its verifier.
+///
+/// Gives the solution instead once the test goes over the allowed attempts.
+async fn test_code(
+    session: &str,
+    code: &Code,
+    crit_id: u8,
+) -> Result<Result<bool, Code>, anyhow::Error> {
+    let request_url = format!(
+        "{}/test?session={session}&code={code}&crits={crit_id}",
+        server_url()
+    );
+
+    let response = reqwest::get(&request_url).await?;
+
+    if !response.status().is_success() {
+        let error: ApiError = response.json().await?;
+        return Err(anyhow::anyhow!(error.message));
+    }
+
+    let TestResults { results, lost } = response.json().await?;
+    if let Some(solution) = lost {
+        return Ok(Err(solution));
+    }
+    results
+        .first()
+        .map(|(_, result)| Ok(*result))
+        .ok_or_else(|| anyhow::anyhow!("The server sent no test result"))
+}
+
+#[derive(Deserialize)]
+struct BidResult {
+    win: bool,
+    bids_left: u32,
+    code: Option<Code>,
+}
+
+#[derive(Deserialize)]
+struct RevealResult {
+    rule: Rule,
+    reveals_left: u8,
+}
+
+async fn reveal_criteria(session: &str, crit_id: u8) -> Result<RevealResult, anyhow::Error> {
+    let request_url = format!("{}/reveal?session={session}&crit={crit_id}", server_url());
+
+    let response = reqwest::get(&request_url).await?;
+
+    if !response.status().is_success() {
+        let error: ApiError = response.json().await?;
+        return Err(anyhow::anyhow!(error.message));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|reqwest_err| reqwest_err.into())
+}
+
+async fn propose_solution(session: &str, solution: &Code) -> Result<BidResult, anyhow::Error> {
+    let request_url = format!("{}/bid?session={session}&code={solution}", server_url());
 
     let response = reqwest::get(&request_url).await?;
 
@@ -107,6 +247,7 @@ async fn get_game_data(base: u8, column_count: u8) -> Result<Game, anyhow::Error
 enum Action {
     TestCode,
     ProposeSolution,
+    Reveal,
     Quit,
 }
 
@@ -116,13 +257,15 @@ impl From<u8> for Action {
             Action::TestCode
         } else if value == 2 {
             Action::ProposeSolution
+        } else if value == 3 {
+            Action::Reveal
         } else {
             Action::Quit
         }
     }
 }
 
-pub fn display_criterias(game: &Game) {
+pub fn display_criterias(game: &PublicGame) {
     for (i, criteria) in game.criterias.iter().enumerate() {
         println!(" {:01}- {}", i, criteria.description);
         for rule in criteria.rules.iter() {
@@ -143,68 +286,128 @@ async fn main() -> Result<()> {
     println!("                 __/ |                     ");
     println!("                |___/                      ");
 
-    server_availability_check().await?;
+    println!("  1- EnigMind, finding the code from criteria");
+    println!("  2- Mastermind, finding the code from guesses alone");
+    let mastermind =
+        read_from_terminal::<u8>("Which game do you want to play [1-2]: ".to_string(), 1, 2) == 2;
 
-    let base = read_from_terminal::<u8>("Please choose a base [1-5] : ".to_string(), 1, 5);
+    if !mastermind {
+        server_availability_check().await?;
+    }
+
+    let base = read_from_terminal::<u8>(
+        format!("Please choose a base [2-{MAX_BASE}] : "),
+        2,
+        MAX_BASE,
+    );
 
     let column_count =
         read_from_terminal::<u8>("Please choose number of columns [1-5] : ".to_string(), 1, 5);
 
+    if mastermind {
+        let max_attempts = match read_from_terminal::<u32>(
+            "Please choose a maximum number of guesses, 0 for unlimited [0-999] : ".to_string(),
+            0,
+            999,
+        ) {
+            0 => None,
+            max_attempts => Some(max_attempts),
+        };
+        return mastermind::play(base, column_count, max_attempts);
+    }
+
+    let difficulty =
+        read_from_terminal::<u8>("Please choose a difficulty [0-100] : ".to_string(), 0, 100);
+
+    let max_attempts = match read_from_terminal::<u32>(
+        "Please choose a maximum number of tests, 0 for unlimited [0-999] : ".to_string(),
+        0,
+        999,
+    ) {
+        0 => None,
+        max_attempts => Some(max_attempts),
+    };
+
+    let reveal_budget = read_from_terminal::<u8>(
+        "Please choose how many criteria verifiers you may reveal, each lowering the score [0-9] : "
+            .to_string(),
+        0,
+        9,
+    );
+
     print!("Waiting for server to generate a secret code");
 
-    let game = select! {
-    res =  timeout(Duration::from_secs(10), get_game_data(base, column_count)) => res,
+    let GeneratedGame { session, game } = select! {
+    res =  timeout(Duration::from_secs(10), get_game_data(base, column_count, difficulty, max_attempts, reveal_budget)) => res,
     _ = print_dot_each_second() => unreachable!()}??;
 
     println!("Done");
+
+    game.configuration
+        .check_schema_version()
+        .map_err(|e| anyhow::anyhow!("The server sent a game this client can't read : {e}"))?;
     //println!("A game was generated ! Secret code : {}", game.code);
 
     let mut total_try_count = 0;
+    // Revealing a criteria again costs nothing
+    let mut revealed_criterias = Vec::new();
 
     let mut quit = false;
 
     display_criterias(&game);
 
+    let start = Instant::now();
+
     while !quit {
         println!("  1- Test a given code against up to 3 criterias");
         println!("  2- Propose a solution");
-        println!("  3- Quit ");
+        println!("  3- Reveal the verifier of a criteria");
+        println!("  4- Quit ");
 
         let main_action: Action =
-            read_from_terminal::<u8>("What do you want to do [1-3]: ".to_string(), 1, 5).into();
+            read_from_terminal::<u8>("What do you want to do [1-4]: ".to_string(), 1, 5).into();
 
         match main_action {
             Action::TestCode => {
-                let code_test =
-                    read_valid_code_from_terminal("Your code to test : ".to_string(), &game);
+                let code_test = read_valid_code_from_terminal(
+                    "Your code to test : ".to_string(),
+                    &game.configuration,
+                );
 
                 let mut try_count = 0;
                 let mut retry = true;
 
                 while retry {
-                    try_count += 1;
-                    total_try_count += 1;
+                    let Some(last_crit) = game.criteria_count().checked_sub(1) else {
+                        println!("This game has no criteria to test against");
+                        break;
+                    };
 
                     let crit_id = read_from_terminal::<u8>(
-                        format!(
-                            "Which criteria to test with your code [0-{}] : ",
-                            game.criterias.len() - 1
-                        ),
+                        format!("Which criteria to test with your code [0-{last_crit}] : "),
                         0,
-                        (game.criterias.len() - 1) as u8,
+                        last_crit,
                     );
 
-                    let criteria = game.criterias[crit_id as usize].clone();
-
+                    let Some(criteria) = game.criterion(crit_id) else {
+                        continue;
+                    };
+
+                    let result = match test_code(&session, &code_test, crit_id).await? {
+                        Ok(result) => result,
+                        Err(solution) => {
+                            println!("No tests left, you lost ! The solution was {solution}");
+                            quit = true;
+                            break;
+                        }
+                    };
+                    try_count += 1;
+                    total_try_count += 1;
                     println!(
                         "Result of your code {} against criteria \"{}\" : {}",
                         code_test.clone(),
                         criteria.description,
-                        criteria
-                            .verif
-                            .rule
-                            .evaluate(code_test.clone())
-                            .unwrap_or(false)
+                        result
                     );
 
                     if try_count < 3 {
@@ -215,17 +418,67 @@ async fn main() -> Result<()> {
                 }
             }
             Action::ProposeSolution => {
-                let solution = read_valid_code_from_terminal("Your solution : ".to_string(), &game);
+                let solution = read_valid_code_from_terminal(
+                    "Your solution : ".to_string(),
+                    &game.configuration,
+                );
 
-                if solution == game.code {
+                let bid = propose_solution(&session, &solution).await?;
+                if bid.win {
                     println!("Well done ! You have found the right solution !");
                     println!(
                         "The solution was, indeed, {}, found with {} tries",
-                        game.code, total_try_count
+                        solution, total_try_count
+                    );
+
+                    let elapsed = start.elapsed();
+                    println!(
+                        "Solved in {} seconds, your score is {}",
+                        elapsed.as_secs(),
+                        compute_score(
+                            total_try_count,
+                            revealed_criterias.len() as u32,
+                            elapsed,
+                            difficulty
+                        )
                     );
                     quit = true;
+                } else if bid.bids_left == 0 {
+                    match bid.code {
+                        Some(solution) => println!(
+                            "Wrong answer, and no solutions left to propose, you lost ! The solution was {solution}"
+                        ),
+                        None => {
+                            println!("Wrong answer, and no solutions left to propose, you lost !")
+                        }
+                    }
+                    quit = true;
                 } else {
-                    println!("Wrong answer !");
+                    println!("Wrong answer ! {} solutions left to propose", bid.bids_left);
+                }
+            }
+            Action::Reveal => {
+                let Some(last_crit) = game.criteria_count().checked_sub(1) else {
+                    println!("This game has no criteria to reveal");
+                    continue;
+                };
+                let crit_id = read_from_terminal::<u8>(
+                    format!("Which criteria to reveal [0-{last_crit}] : "),
+                    0,
+                    last_crit,
+                );
+
+                match reveal_criteria(&session, crit_id).await {
+                    Ok(reveal) => {
+                        if !revealed_criterias.contains(&crit_id) {
+                            revealed_criterias.push(crit_id);
+                        }
+                        println!(
+                            "Criteria {crit_id} is verified by {} ({} reveals left)",
+                            reveal.rule, reveal.reveals_left
+                        );
+                    }
+                    Err(e) => println!("{e}"),
                 }
             }
             Action::Quit => quit = true,
@@ -234,3 +487,28 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_url, DEFAULT_SERVER};
+
+    #[test]
+    fn test_generate_url() {
+        assert_eq!(
+            generate_url(DEFAULT_SERVER, 5, 3, 0, None, 0),
+            "http://localhost:3000/generate?base=5&column_count=3&difficulty=0"
+        );
+        assert_eq!(
+            generate_url("https://enigmind.example.org:8080", 4, 2, 60, None, 0),
+            "https://enigmind.example.org:8080/generate?base=4&column_count=2&difficulty=60"
+        );
+        assert_eq!(
+            generate_url(DEFAULT_SERVER, 5, 3, 0, Some(10), 0),
+            "http://localhost:3000/generate?base=5&column_count=3&difficulty=0&max_attempts=10"
+        );
+        assert_eq!(
+            generate_url(DEFAULT_SERVER, 5, 3, 0, None, 2),
+            "http://localhost:3000/generate?base=5&column_count=3&difficulty=0&reveal_budget=2"
+        );
+    }
+}
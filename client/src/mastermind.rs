@@ -0,0 +1,156 @@
+use anyhow::Result;
+use enigmind_lib::{
+    code::Code,
+    setup::{generate_secret, GameConfiguration, SCHEMA_VERSION},
+};
+
+use crate::read_valid_code_from_terminal;
+
+/// Outcome of a guess in a Mastermind game.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Turn {
+    /// Digits at the right place, and right digits at the wrong place
+    Feedback(u8, u8),
+    Won,
+    Lost,
+}
+
+/// Classic Mastermind: no criteria, the player guesses codes and is told how close they are.
+pub struct Mastermind {
+    pub configuration: GameConfiguration,
+    secret: Code,
+    attempts: u32,
+}
+
+impl Mastermind {
+    pub fn new(configuration: GameConfiguration, secret: Code) -> Self {
+        Self {
+            configuration,
+            secret,
+            attempts: 0,
+        }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn secret(&self) -> &Code {
+        &self.secret
+    }
+
+    pub fn guess(&mut self, guess: &Code) -> Turn {
+        self.attempts += 1;
+
+        if *guess == self.secret {
+            return Turn::Won;
+        }
+        if self.configuration.is_lost(self.attempts + 1) {
+            return Turn::Lost;
+        }
+        let (exact, misplaced) = self.secret.feedback(guess);
+        Turn::Feedback(exact, misplaced)
+    }
+}
+
+pub fn play(base: u8, column_count: u8, max_attempts: Option<u32>) -> Result<()> {
+    let configuration = GameConfiguration {
+        schema_version: SCHEMA_VERSION,
+        column_count,
+        base,
+        min_difficulty: 0,
+        max_attempts,
+        generation: None,
+    };
+    let secret = generate_secret(base, column_count, None)
+        .map_err(|e| anyhow::anyhow!("Cannot generate a secret code : {e}"))?;
+    let mut game = Mastermind::new(configuration, secret);
+
+    println!("Find the secret code, each guess telling how many digits are right");
+    loop {
+        let guess = read_valid_code_from_terminal(
+            format!("Guess #{} : ", game.attempts() + 1),
+            &game.configuration,
+        );
+
+        match game.guess(&guess) {
+            Turn::Feedback(exact, misplaced) => {
+                println!("{exact} at the right place, {misplaced} at the wrong place")
+            }
+            Turn::Won => {
+                println!(
+                    "Well done ! You have found {} in {} guesses",
+                    guess,
+                    game.attempts()
+                );
+                return Ok(());
+            }
+            Turn::Lost => {
+                println!(
+                    "No guesses left, you lost ! The solution was {}",
+                    game.secret()
+                );
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mastermind, Turn};
+    use enigmind_lib::{
+        code::Code,
+        setup::{GameConfiguration, SCHEMA_VERSION},
+    };
+
+    fn game(max_attempts: Option<u32>) -> Mastermind {
+        let configuration = GameConfiguration {
+            schema_version: SCHEMA_VERSION,
+            column_count: 4,
+            base: 6,
+            min_difficulty: 0,
+            max_attempts,
+            generation: None,
+        };
+        Mastermind::new(configuration, Code::new(vec![1, 1, 2, 2]))
+    }
+
+    #[test]
+    fn test_scripted_game_is_won() {
+        let mut game = game(None);
+
+        let turns: Vec<Turn> = ["0000", "1100", "2211", "1212", "1122"]
+            .iter()
+            .map(|guess| game.guess(&guess.parse().unwrap()))
+            .collect();
+
+        assert_eq!(
+            turns,
+            vec![
+                Turn::Feedback(0, 0),
+                Turn::Feedback(2, 0),
+                Turn::Feedback(0, 4),
+                Turn::Feedback(2, 2),
+                Turn::Won,
+            ]
+        );
+        assert_eq!(game.attempts(), 5);
+    }
+
+    #[test]
+    fn test_last_wrong_guess_loses() {
+        let mut game = game(Some(2));
+
+        assert_eq!(game.guess(&"0000".parse().unwrap()), Turn::Feedback(0, 0));
+        assert_eq!(game.guess(&"1100".parse().unwrap()), Turn::Lost);
+    }
+
+    #[test]
+    fn test_last_guess_can_win() {
+        let mut game = game(Some(2));
+
+        assert_eq!(game.guess(&"0000".parse().unwrap()), Turn::Feedback(0, 0));
+        assert_eq!(game.guess(&"1122".parse().unwrap()), Turn::Won);
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+/// Tries a revealed verifier costs, as it gives away more than any single test.
+const REVEAL_COST: u64 = 3;
+
+/// Fewer tries, reveals and less time give a higher score, harder games weighing more.
+pub fn compute_score(tries: u32, reveals: u32, elapsed: Duration, difficulty: u8) -> u32 {
+    let weight = 100 + difficulty.min(100) as u64;
+    let cost = (1 + tries as u64 + REVEAL_COST * reveals as u64) * (60 + elapsed.as_secs());
+
+    (weight * 1_000_000 / cost).min(u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compute_score;
+    use std::time::Duration;
+
+    #[test]
+    fn test_more_tries_lower_the_score() {
+        let elapsed = Duration::from_secs(90);
+
+        for tries in 0..20 {
+            assert!(
+                compute_score(tries + 1, 0, elapsed, 50) < compute_score(tries, 0, elapsed, 50)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reveals_lower_the_score() {
+        let elapsed = Duration::from_secs(90);
+
+        for reveals in 0..5 {
+            assert!(
+                compute_score(5, reveals + 1, elapsed, 50) < compute_score(5, reveals, elapsed, 50)
+            );
+        }
+        // A reveal costs more than a test
+        assert!(compute_score(5, 1, elapsed, 50) < compute_score(6, 0, elapsed, 50));
+    }
+
+    #[test]
+    fn test_more_time_lowers_the_score() {
+        let score = |secs| compute_score(5, 0, Duration::from_secs(secs), 50);
+
+        assert!(score(120) < score(60));
+        assert!(score(600) < score(120));
+    }
+
+    #[test]
+    fn test_difficulty_raises_the_score() {
+        let elapsed = Duration::from_secs(90);
+
+        for difficulty in [0, 20, 40, 60, 80] {
+            assert!(
+                compute_score(5, 0, elapsed, difficulty + 20)
+                    > compute_score(5, 0, elapsed, difficulty)
+            );
+        }
+    }
+}
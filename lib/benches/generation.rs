@@ -0,0 +1,80 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use enigmind_lib::{
+    rule::{Operator, Rule},
+    setup::{generate_game_seeded, GameConfiguration, SCHEMA_VERSION},
+};
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    group.sample_size(10);
+
+    group.bench_function("candidate masks 5x4", |b| {
+        let gc = GameConfiguration {
+            schema_version: SCHEMA_VERSION,
+            column_count: 4,
+            base: 5,
+            min_difficulty: 20,
+            max_attempts: None,
+            generation: None,
+        };
+        b.iter(|| {
+            gc.all_candidate_rules()
+                .unwrap()
+                .iter()
+                .map(|rule| rule.get_mask(&gc).unwrap().count_ones())
+                .sum::<usize>()
+        })
+    });
+
+    group.bench_function("sum and parity masks 5x5", |b| {
+        let gc = GameConfiguration {
+            schema_version: SCHEMA_VERSION,
+            column_count: 5,
+            base: 5,
+            min_difficulty: 0,
+            max_attempts: None,
+            generation: None,
+        };
+        let rules: Vec<Rule> = gc
+            .all_candidate_rules()
+            .unwrap()
+            .iter()
+            .filter(|rule| {
+                matches!(
+                    rule,
+                    Rule::MatchesOp(
+                        Operator::Pair
+                            | Operator::Impair
+                            | Operator::SameParity
+                            | Operator::SumBelow(_)
+                            | Operator::SumEquals(_)
+                            | Operator::SumAbove(_)
+                            | Operator::SumMultipleOf(_)
+                            | Operator::SumBetween(..),
+                        _
+                    )
+                )
+            })
+            .cloned()
+            .collect();
+        b.iter(|| {
+            rules
+                .iter()
+                .map(|rule| rule.get_mask(&gc).unwrap().count_ones())
+                .sum::<usize>()
+        })
+    });
+
+    group.bench_function("game 5x4", |b| {
+        let mut seed = 0;
+        b.iter(|| {
+            seed += 1;
+            generate_game_seeded(5, 4, 20, seed).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, generation);
+criterion_main!(benches);
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    code::Code,
+    criteria::Criteria,
+    criterias::Criterias,
+    error::EnigmindError,
+    rule::Rule,
+    rules::Rules,
+    setup::{Game, GameConfiguration, PublicGame},
+    verifier::Verifier,
+};
+
+// First byte of the encoded games, telling whether masks follow
+const WITH_MASKS: u8 = 0;
+const WITHOUT_MASKS: u8 = 1;
+
+/// A criteria without its verifier mask, which makes most of an encoded game.
+#[derive(Serialize, Deserialize)]
+struct CompactCriteria {
+    id: u8,
+    rule: Rule,
+    description: String,
+    rules: Rules,
+    info_bits: f64,
+}
+
+fn compact(criterias: &Criterias) -> Vec<CompactCriteria> {
+    criterias
+        .iter()
+        .map(|crit| CompactCriteria {
+            id: crit.id,
+            rule: crit.verif.rule.clone(),
+            description: crit.description.clone(),
+            rules: crit.rules.clone(),
+            info_bits: crit.info_bits,
+        })
+        .collect()
+}
+
+fn expand(
+    gc: &GameConfiguration,
+    criterias: Vec<CompactCriteria>,
+) -> Result<Criterias, EnigmindError> {
+    criterias
+        .into_iter()
+        .map(|crit| {
+            Ok(Criteria {
+                id: crit.id,
+                verif: Verifier::new(gc, crit.rule)?,
+                description: crit.description,
+                rules: crit.rules,
+                info_bits: crit.info_bits,
+            })
+        })
+        .collect::<Result<Vec<_>, EnigmindError>>()
+        .map(Criterias::from)
+}
+
+fn encode<T: Serialize>(flag: u8, value: &T) -> Result<Vec<u8>, EnigmindError> {
+    let bytes =
+        rmp_serde::to_vec_named(value).map_err(|e| EnigmindError::ParseError(e.to_string()))?;
+    Ok([vec![flag], bytes].concat())
+}
+
+fn decode<T: for<'a> Deserialize<'a>>(bytes: &[u8]) -> Result<T, EnigmindError> {
+    rmp_serde::from_slice(bytes).map_err(|e| EnigmindError::ParseError(e.to_string()))
+}
+
+fn split_flag(bytes: &[u8]) -> Result<(u8, &[u8]), EnigmindError> {
+    match bytes.split_first() {
+        Some((flag, rest)) if *flag == WITH_MASKS || *flag == WITHOUT_MASKS => Ok((*flag, rest)),
+        _ => Err(EnigmindError::ParseError("Not an encoded game".to_string())),
+    }
+}
+
+impl Game {
+    /// Compact binary encoding, leaving out the verifier masks when `recompute_masks` is set.
+    pub fn to_bytes(&self, recompute_masks: bool) -> Result<Vec<u8>, EnigmindError> {
+        if recompute_masks {
+            encode(
+                WITHOUT_MASKS,
+                &(
+                    &self.configuration,
+                    compact(&self.criterias),
+                    &self.code,
+                    self.seed,
+                ),
+            )
+        } else {
+            encode(WITH_MASKS, self)
+        }
+    }
+
+    /// Decodes `Game::to_bytes`, recomputing the masks when they were left out.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Game, EnigmindError> {
+        let (flag, bytes) = split_flag(bytes)?;
+        if flag == WITH_MASKS {
+            return decode(bytes);
+        }
+
+        let (configuration, criterias, code, seed): (
+            GameConfiguration,
+            Vec<CompactCriteria>,
+            Code,
+            u64,
+        ) = decode(bytes)?;
+        Ok(Game {
+            criterias: expand(&configuration, criterias)?,
+            configuration,
+            code,
+            seed,
+        })
+    }
+}
+
+impl PublicGame {
+    /// Compact binary encoding of what players see, holding neither the code nor the verifiers.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EnigmindError> {
+        encode(WITHOUT_MASKS, self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<PublicGame, EnigmindError> {
+        let (_, bytes) = split_flag(bytes)?;
+        decode(bytes)
+    }
+}
+
+#[cfg(all(test, feature = "generation"))]
+mod tests {
+    use crate::setup::{generate_game_seeded, Game, PublicGame};
+
+    #[test]
+    fn test_round_trip() {
+        for seed in 0..3 {
+            let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+            let json = serde_json::to_string(&game).unwrap();
+
+            let full = game.to_bytes(false).unwrap();
+            let compact = game.to_bytes(true).unwrap();
+            assert!(full.len() < json.len());
+            assert!(compact.len() < full.len());
+
+            for bytes in [full, compact] {
+                let other = Game::from_bytes(&bytes).unwrap();
+                assert_eq!(serde_json::to_string(&other).unwrap(), json);
+            }
+
+            let public = PublicGame::from(&game);
+            let other = PublicGame::from_bytes(&public.to_bytes().unwrap()).unwrap();
+            assert_eq!(
+                serde_json::to_string(&other).unwrap(),
+                serde_json::to_string(&public).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_public_bytes_hide_verifiers() {
+        let game = generate_game_seeded(5, 3, 20, 1).unwrap();
+        let bytes = PublicGame::from(&game).to_bytes().unwrap();
+
+        // MessagePack describes itself, so the fields can be read back without the type
+        let value: serde_json::Value = rmp_serde::from_slice(&bytes[1..]).unwrap();
+        assert!(value.get("code").is_none());
+        assert!(value.get("seed").is_none());
+        for crit in value["criterias"].as_array().unwrap() {
+            assert!(crit.get("verif").is_none());
+            assert!(crit.get("rule").is_none());
+            assert!(crit.get("rules").is_some());
+        }
+        assert!(Game::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        assert!(Game::from_bytes(&[]).is_err());
+        assert!(Game::from_bytes(&[7, 0, 0]).is_err());
+        assert!(Game::from_bytes(&[1, 0, 0]).is_err());
+    }
+}
@@ -1,38 +1,27 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{column::Column, error::EnigmindError, setup::GameConfiguration};
-use std::fmt;
+use std::{collections::HashMap, fmt, str::FromStr};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Code(pub Vec<u8>);
 
-impl From<u32> for Code {
-    fn from(code: u32) -> Self {
-        let mut c = code;
-        let mut v = Vec::new();
-
-        while c > 0 {
-            v.push((c % 10) as u8);
+impl FromStr for Code {
+    type Err = EnigmindError;
 
-            c /= 10;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(EnigmindError::EmptyCode);
         }
 
-        v.reverse();
-        Code::new(v)
-    }
-}
-
-impl From<String> for Code {
-    fn from(value: String) -> Self {
         let mut v = Vec::new();
 
-        for c in value.chars() {
-            if c.is_ascii_digit() {
-                v.push(c.to_digit(10).unwrap_or(0) as u8);
-            }
+        for c in s.chars() {
+            v.push(c.to_digit(10).ok_or(EnigmindError::InvalidDigit(c))? as u8);
         }
 
-        Code::new(v)
+        Ok(Code::new(v))
     }
 }
 
@@ -41,6 +30,19 @@ impl Code {
         Self(v)
     }
 
+    pub fn from_digits_str(s: &str, gc: &GameConfiguration) -> Result<Self, EnigmindError> {
+        let code: Code = s.parse()?;
+
+        match code.0.iter().find(|digit| **digit >= gc.base) {
+            Some(digit) => Err(EnigmindError::DigitOutOfBounds(*digit)),
+            None => Ok(code),
+        }
+    }
+
+    pub fn is_well_formed(&self, gc: &GameConfiguration) -> bool {
+        self.0.len() == gc.column_count as usize && self.0.iter().all(|&d| d < gc.base)
+    }
+
     pub fn get(&self, c: Column) -> Result<u8, EnigmindError> {
         let index: usize = c.into();
         self.0
@@ -69,14 +71,73 @@ impl Code {
         code_vec.reverse();
         Code::new(code_vec)
     }
+
+    /// Mastermind scoring of a guess against this code, as (digits at the right place,
+    /// right digits at the wrong place), each digit of either code counted at most once.
+    /// Codes are expected to have the same length, extra digits being scored as wrong.
+    pub fn feedback(&self, guess: &Code) -> (u8, u8) {
+        let mut exact = 0;
+        let mut secret_left = HashMap::new();
+        let mut guess_left = Vec::new();
+
+        for (secret_digit, guess_digit) in self.0.iter().zip(guess.0.iter()) {
+            if secret_digit == guess_digit {
+                exact += 1;
+            } else {
+                *secret_left.entry(*secret_digit).or_insert(0u8) += 1;
+                guess_left.push(*guess_digit);
+            }
+        }
+
+        let mut misplaced = 0;
+        for digit in guess_left {
+            if let Some(count) = secret_left.get_mut(&digit).filter(|count| **count > 0) {
+                *count -= 1;
+                misplaced += 1;
+            }
+        }
+
+        (exact, misplaced)
+    }
 }
 
-impl PartialEq for Code {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+/// Every code of a configuration, in shift order, built one at a time.
+pub struct Codes<'a> {
+    gc: &'a GameConfiguration,
+    shift: u32,
+    end: u32,
+}
+
+impl<'a> Codes<'a> {
+    pub fn new(gc: &'a GameConfiguration) -> Self {
+        Self {
+            gc,
+            shift: 0,
+            end: gc.solution_count(),
+        }
     }
 }
 
+impl Iterator for Codes<'_> {
+    type Item = Code;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.shift >= self.end {
+            return None;
+        }
+        let code = Code::from_shift(self.shift, self.gc);
+        self.shift += 1;
+        Some(code)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.shift) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Codes<'_> {}
+
 impl fmt::Display for Code {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for c in &self.0 {
@@ -85,3 +146,149 @@ impl fmt::Display for Code {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Code;
+    use crate::{
+        error::EnigmindError,
+        setup::{test_configuration, GameConfiguration},
+    };
+    use std::{
+        collections::{hash_map::DefaultHasher, HashSet},
+        hash::{Hash, Hasher},
+    };
+
+    fn config(base: u8) -> GameConfiguration {
+        test_configuration(3, base)
+    }
+
+    #[test]
+    fn test_from_digits_str() {
+        for base in 2..=9 {
+            let gc = config(base);
+            let highest = base - 1;
+
+            let code = Code::from_digits_str(&format!("0{highest}1"), &gc).unwrap();
+            assert_eq!(code, Code::new(vec![0, highest, 1]));
+
+            assert!(matches!(
+                Code::from_digits_str(&format!("0{base}1"), &gc),
+                Err(EnigmindError::DigitOutOfBounds(d)) if d == base
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_digits_str_invalid_char() {
+        assert!(matches!(
+            Code::from_digits_str("1a2", &config(5)),
+            Err(EnigmindError::InvalidDigit('a'))
+        ));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("0429".parse::<Code>().unwrap(), Code::new(vec![0, 4, 2, 9]));
+
+        assert!(matches!("".parse::<Code>(), Err(EnigmindError::EmptyCode)));
+        assert!(matches!(
+            "12 3".parse::<Code>(),
+            Err(EnigmindError::InvalidDigit(' '))
+        ));
+        assert!(matches!(
+            " 123".parse::<Code>(),
+            Err(EnigmindError::InvalidDigit(' '))
+        ));
+        assert!(matches!(
+            "12x".parse::<Code>(),
+            Err(EnigmindError::InvalidDigit('x'))
+        ));
+        assert!(matches!(
+            "-12".parse::<Code>(),
+            Err(EnigmindError::InvalidDigit('-'))
+        ));
+    }
+
+    #[test]
+    fn test_shift_round_trip() {
+        for base in 2..=5 {
+            let gc = config(base);
+
+            for shift in 0..gc.solution_count() {
+                let code = Code::from_shift(shift, &gc);
+
+                assert!(code.is_well_formed(&gc));
+                assert_eq!(code.get_shift(&gc), shift);
+                assert_eq!(Code::from_shift(code.get_shift(&gc), &gc), code);
+            }
+        }
+
+        let gc = config(5);
+        let code = Code::new(vec![0, 0, 4]);
+        assert_eq!(code.get_shift(&gc), 4);
+        assert_eq!(Code::from_shift(4, &gc), code);
+    }
+
+    #[test]
+    fn test_is_well_formed() {
+        let gc = config(5);
+
+        assert!(Code::new(vec![0, 0, 0]).is_well_formed(&gc));
+        assert!(!Code::new(vec![0, 0]).is_well_formed(&gc));
+        assert!(!Code::new(vec![0, 0, 0, 0]).is_well_formed(&gc));
+        assert!(!Code::new(vec![0, 5, 0]).is_well_formed(&gc));
+    }
+
+    #[test]
+    fn test_codes() {
+        let gc = config(4);
+        let codes = gc.codes();
+        assert_eq!(codes.len(), gc.solution_count() as usize);
+
+        let codes: Vec<Code> = codes.collect();
+        let mut manual = Vec::new();
+        for shift in 0..gc.solution_count() {
+            manual.push(Code::from_shift(shift, &gc));
+        }
+        assert_eq!(codes, manual);
+
+        let distinct: HashSet<&Code> = codes.iter().collect();
+        assert_eq!(distinct.len(), gc.solution_count() as usize);
+    }
+
+    #[test]
+    fn test_eq_and_hash() {
+        let hash = |code: &Code| {
+            let mut hasher = DefaultHasher::new();
+            code.hash(&mut hasher);
+            hasher.finish()
+        };
+        let code = Code::new(vec![1, 0, 2]);
+        let same: Code = "102".parse().unwrap();
+
+        assert_eq!(code, same);
+        assert_eq!(hash(&code), hash(&same));
+        assert_ne!(code, Code::new(vec![1, 0]));
+
+        let guesses: HashSet<Code> = [code, same, Code::new(vec![2, 0, 1])].into();
+        assert_eq!(guesses.len(), 2);
+    }
+
+    #[test]
+    fn test_feedback() {
+        let secret = Code::new(vec![1, 1, 2, 2]);
+
+        assert_eq!(secret.feedback(&secret), (4, 0));
+        assert_eq!(secret.feedback(&Code::new(vec![3, 4, 5, 6])), (0, 0));
+        assert_eq!(secret.feedback(&Code::new(vec![2, 2, 1, 1])), (0, 4));
+        assert_eq!(secret.feedback(&Code::new(vec![1, 2, 1, 3])), (1, 2));
+        // Only two 1s to match, whatever the guess holds
+        assert_eq!(secret.feedback(&Code::new(vec![3, 3, 1, 1])), (0, 2));
+        assert_eq!(secret.feedback(&Code::new(vec![1, 1, 1, 1])), (2, 0));
+        assert_eq!(
+            Code::new(vec![0, 4, 2]).feedback(&Code::new(vec![2, 4, 0])),
+            (1, 2)
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use std::{fmt, hash::Hash};
+use std::{fmt, hash::Hash, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::EnigmindError;
+
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Column(u8);
 
 impl From<Column> for u8 {
@@ -23,7 +26,17 @@ impl From<Column> for usize {
     }
 }
 
-impl Column {}
+impl Column {
+    /// Column of a letter from 'A', in either case.
+    pub fn from_char(c: char) -> Option<Column> {
+        let c = c.to_ascii_uppercase();
+        c.is_ascii_uppercase().then(|| Column(c as u8 - b'A'))
+    }
+
+    pub fn to_char(&self) -> char {
+        (b'A' + self.0) as char
+    }
+}
 
 /*impl Hash for Column {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -31,9 +44,23 @@ impl Column {}
     }
 }*/
 
+/// Reads back a column as displayed, a letter from 'A'.
+impl FromStr for Column {
+    type Err = EnigmindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let column = match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_uppercase() => Column::from_char(c),
+            _ => None,
+        };
+        column.ok_or_else(|| EnigmindError::ParseError(format!("unknown column '{s}'")))
+    }
+}
+
 impl fmt::Display for Column {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", (self.0 + 65) as char)
+        write!(f, "{}", self.to_char())
     }
 }
 
@@ -77,3 +104,27 @@ impl fmt::Display for Columns {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::Column;
+
+    #[test]
+    fn test_chars() {
+        assert_eq!(Column::from_char('A'), Some(Column::from(0)));
+        assert_eq!(Column::from_char('a'), Some(Column::from(0)));
+        assert_eq!(Column::from_char('c'), Some(Column::from(2)));
+        assert_eq!(Column::from_char('Z'), Some(Column::from(25)));
+        assert_eq!(Column::from_char('['), None);
+        assert_eq!(Column::from_char('@'), None);
+        assert_eq!(Column::from_char('1'), None);
+        assert_eq!(Column::from_char('é'), None);
+
+        for index in 0..26 {
+            let column = Column::from(index);
+            assert_eq!(Column::from_char(column.to_char()), Some(column));
+        }
+        assert_eq!(Column::from(25).to_char(), 'Z');
+        assert_eq!(Column::from(2).to_string(), "C");
+    }
+}
@@ -1,14 +1,16 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::column::Column;
+use crate::{column::Column, error::EnigmindError};
 use std::{
     collections::HashSet,
     fmt,
     hash::Hash,
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ColumnSet(HashSet<Column>);
 
 impl ColumnSet {
@@ -18,6 +20,19 @@ impl ColumnSet {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    pub fn sorted(&self) -> Vec<Column> {
+        let mut columns: Vec<Column> = self.0.iter().copied().collect();
+        columns.sort();
+        columns
+    }
+}
+
+// Columns are serialized in ascending order so that equal sets always give the same output,
+// which seeded generation relies on
+impl Serialize for ColumnSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.sorted())
+    }
 }
 
 impl Deref for ColumnSet {
@@ -53,9 +68,10 @@ impl PartialEq for ColumnSet {
 }
 impl Eq for ColumnSet {}
 
+// Hashing the sorted columns keeps equal sets hashing equally whatever their iteration order
 impl Hash for ColumnSet {
-    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {
-        self.0.hasher();
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.sorted().hash(state);
     }
 }
 
@@ -63,7 +79,7 @@ impl fmt::Display for ColumnSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[")?;
         let mut first = true;
-        for r in &self.0 {
+        for r in self.sorted() {
             if !first {
                 write!(f, ", ")?;
             }
@@ -75,3 +91,84 @@ impl fmt::Display for ColumnSet {
         Ok(())
     }
 }
+
+/// Reads back a set as displayed, e.g. "[A, C]".
+impl FromStr for ColumnSet {
+    type Err = EnigmindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let columns = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| EnigmindError::ParseError(format!("unknown column set '{s}'")))?;
+
+        match columns.trim() {
+            "" => Ok(HashSet::new().into()),
+            columns => columns
+                .split(',')
+                .map(|column| column.trim().parse::<Column>())
+                .collect::<Result<HashSet<_>, _>>()
+                .map(ColumnSet),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColumnSet;
+    use crate::column::Column;
+    use std::{
+        collections::{hash_map::DefaultHasher, HashSet},
+        hash::{Hash, Hasher},
+    };
+
+    fn hash(cs: &ColumnSet) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        cs.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_display_is_sorted() {
+        let forward: ColumnSet = HashSet::from_iter((0..4).map(Column::from)).into();
+        let backward: ColumnSet = HashSet::from_iter((0..4).rev().map(Column::from)).into();
+
+        assert_eq!(forward, backward);
+        assert_eq!(forward.to_string(), "[A, B, C, D]");
+        assert_eq!(forward.to_string(), backward.to_string());
+    }
+
+    #[test]
+    fn test_parse() {
+        let cs: ColumnSet = "[A, C]".parse().unwrap();
+        assert_eq!(cs, HashSet::from_iter([0, 2].map(Column::from)).into());
+        assert_eq!(cs.to_string().parse::<ColumnSet>().unwrap(), cs);
+        assert!("[]".parse::<ColumnSet>().unwrap().is_empty());
+
+        for garbage in ["A, C", "[A, c]", "[AB]", "[A,]"] {
+            assert!(garbage.parse::<ColumnSet>().is_err(), "{garbage}");
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        let forward: ColumnSet = HashSet::from_iter((0..4).map(Column::from)).into();
+        let backward: ColumnSet = HashSet::from_iter((0..4).rev().map(Column::from)).into();
+        assert_eq!(hash(&forward), hash(&backward));
+
+        let mut sets = HashSet::new();
+        let mut hashes = HashSet::new();
+        for bits in 1u8..32 {
+            let cs: ColumnSet =
+                HashSet::from_iter((0..5).filter(|i| bits & (1 << i) != 0).map(Column::from))
+                    .into();
+            hashes.insert(hash(&cs));
+            sets.insert(cs.clone());
+            sets.insert(cs);
+        }
+
+        assert_eq!(sets.len(), 31);
+        assert_eq!(hashes.len(), 31);
+    }
+}
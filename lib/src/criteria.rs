@@ -2,18 +2,88 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{rules::Rules, verifier::Verifier};
+use crate::{
+    code::Code, error::EnigmindError, rule::Rule, rules::Rules, setup::GameConfiguration,
+    verifier::Verifier,
+};
+use nbitmask::BitMask;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Criteria {
+    pub id: u8,
     pub verif: Verifier,
     pub description: String,
     pub rules: Rules,
+    /// How much the verifier narrows the possible codes, see `Verifier::info_bits`
+    #[serde(default)]
+    pub info_bits: f64,
+}
+
+impl Criteria {
+    /// The codes each rule shown to the player would accept, the verifier being one of them.
+    pub fn candidate_masks(
+        &self,
+        gc: &GameConfiguration,
+    ) -> Result<Vec<(Rule, BitMask<u64>)>, EnigmindError> {
+        self.rules
+            .iter()
+            .map(|rule| Ok((rule.clone(), rule.get_mask(gc)?)))
+            .collect()
+    }
+
+    /// Codes the verifier accepts, lowest shift first. Tells what the verifier is, so it is
+    /// for explaining a game rather than for showing while it is played.
+    pub fn accepted_codes(&self, gc: &GameConfiguration) -> Vec<Code> {
+        mask_codes(self.verif.mask.clone(), gc)
+    }
+
+    /// Codes the verifier rules out, lowest shift first.
+    pub fn rejected_codes(&self, gc: &GameConfiguration) -> Vec<Code> {
+        let mut rejected = BitMask::ones(gc.solution_count() as usize);
+        for code in self.accepted_codes(gc) {
+            if rejected.set(code.get_shift(gc) as usize, false).is_err() {
+                break;
+            }
+        }
+        mask_codes(rejected, gc)
+    }
+}
+
+/// What players see of a criteria: its description and candidate rules, not the verifier.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PublicCriteria {
+    pub id: u8,
+    pub description: String,
+    pub rules: Rules,
+}
+
+impl From<&Criteria> for PublicCriteria {
+    fn from(crit: &Criteria) -> Self {
+        Self {
+            id: crit.id,
+            description: crit.description.clone(),
+            rules: crit.rules.clone(),
+        }
+    }
+}
+
+/// Codes of the set bits, read lowest first and cleared once read.
+pub(crate) fn mask_codes(mut mask: BitMask<u64>, gc: &GameConfiguration) -> Vec<Code> {
+    let mut codes = Vec::new();
+    for _ in 0..mask.count_ones() {
+        let shift = mask.trailing_zeros();
+        codes.push(Code::from_shift(shift as u32, gc));
+        if mask.set(shift, false).is_err() {
+            break;
+        }
+    }
+    codes
 }
 
 impl fmt::Display for Criteria {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Criteria : {}.", self.description)?;
+        writeln!(f, "Criteria {} : {}.", self.id, self.description)?;
         writeln!(f, "Rules : {} {}.", self.verif.rule, self.verif.mask)?;
         for rule in self.rules.iter() {
             write!(f, "\t{rule}")?;
@@ -1,11 +1,13 @@
-use std::{fmt, ops::Deref};
+use std::{
+    fmt,
+    ops::{Deref, Index},
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::criteria::Criteria;
 
 #[derive(Clone, Serialize, Deserialize)]
-
 pub struct Criterias(Vec<Criteria>);
 
 impl From<Criterias> for Vec<Criteria> {
@@ -31,6 +33,41 @@ impl FromIterator<Criteria> for Criterias {
     }
 }
 
+impl Criterias {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Criteria> {
+        self.0.get(index)
+    }
+
+    /// The criteria with the given id, if any.
+    pub fn criterion(&self, id: u8) -> Option<&Criteria> {
+        self.0.iter().find(|crit| crit.id == id)
+    }
+
+    /// Orders the criteria whose verifier accepts the fewest codes first, ties keeping their
+    /// order. Ids stay with their criteria. The order tells about the verifiers, so it is
+    /// for reviewing a game rather than for presenting it to a player.
+    pub fn sort_by_selectivity(&mut self) {
+        self.0.sort_by_key(|crit| crit.verif.mask.count_ones());
+    }
+}
+
+/// Panics when out of bounds, `get` and `criterion` being the checked lookups.
+impl Index<usize> for Criterias {
+    type Output = Criteria;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
 impl Deref for Criterias {
     type Target = Vec<Criteria>;
 
@@ -47,3 +84,85 @@ impl fmt::Display for Criterias {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Criterias;
+    use crate::{
+        column::Column,
+        columns::ColumnSet,
+        criteria::Criteria,
+        rule::{Operator, Rule},
+        setup::{test_configuration, GameConfiguration},
+        verifier::Verifier,
+    };
+    use std::{cmp::Ordering, collections::HashSet};
+
+    fn columns(indexes: &[u8]) -> ColumnSet {
+        HashSet::from_iter(indexes.iter().map(|i| Column::from(*i))).into()
+    }
+
+    fn gc() -> GameConfiguration {
+        test_configuration(2, 3)
+    }
+
+    fn criterias(rules: Vec<Rule>) -> Criterias {
+        rules
+            .into_iter()
+            .enumerate()
+            .map(|(id, rule)| Criteria {
+                id: id as u8,
+                verif: Verifier::new(&gc(), rule.clone()).unwrap(),
+                description: rule.to_string(),
+                rules: vec![rule].into(),
+                info_bits: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_access() {
+        let criterias = criterias(
+            (0..2)
+                .map(|id| Rule::ColumnEquals(Column::from(id), 1))
+                .collect(),
+        );
+
+        assert_eq!(criterias.len(), 2);
+        assert!(!criterias.is_empty());
+        assert_eq!(criterias[1].id, 1);
+        assert_eq!(criterias.get(0).unwrap().description, "A == 1");
+        assert!(criterias.get(2).is_none());
+
+        let empty: Criterias = Vec::new().into();
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert!(empty.get(0).is_none());
+    }
+
+    #[test]
+    fn test_sort_by_selectivity() {
+        let mut criterias = criterias(vec![
+            Rule::MatchesOp(Operator::SumAbove(0), columns(&[0, 1])),
+            Rule::ColumnEquals(Column::from(0), 1),
+            Rule::MatchesOp(Operator::SumEquals(4), columns(&[0, 1])),
+            Rule::ColumnCompare {
+                left: Column::from(0),
+                right: Column::from(1),
+                ordering: Ordering::Less,
+            },
+        ]);
+        criterias.sort_by_selectivity();
+
+        let counts: Vec<usize> = criterias
+            .iter()
+            .map(|crit| crit.verif.mask.count_ones())
+            .collect();
+        assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(
+            criterias.iter().map(|crit| crit.id).collect::<Vec<_>>(),
+            vec![2, 1, 3, 0]
+        );
+        assert_eq!(criterias.criterion(0).unwrap().verif.mask.count_ones(), 8);
+    }
+}
@@ -5,6 +5,20 @@ use std::fmt;
 pub enum EnigmindError {
     BitmaskError(BitMaskError),
     ColumnIndexOutOfBounds,
+    EmptyCode,
+    InvalidDigit(char),
+    DigitOutOfBounds(u8),
+    WrongCodeLength { expected: u8, found: usize },
+    ConfigurationTooLarge,
+    InvalidConfiguration { base: u8, column_count: u8 },
+    InvalidRange { low: u8, high: u8 },
+    NoRulesAvailable,
+    NoUniqueSolution,
+    CannotSatisfyConstraints,
+    GenerationTimeout,
+    UnsupportedSchemaVersion(u16),
+    ParseError(String),
+    InvalidTestResult(String),
 }
 
 impl From<BitMaskError> for EnigmindError {
@@ -18,6 +32,33 @@ impl fmt::Display for EnigmindError {
         match &self {
             EnigmindError::BitmaskError(err) => write!(f, "{err}"),
             EnigmindError::ColumnIndexOutOfBounds => write!(f, "ColumnIndexOutOfBounds"),
+            EnigmindError::EmptyCode => write!(f, "EmptyCode"),
+            EnigmindError::InvalidDigit(c) => write!(f, "InvalidDigit({c})"),
+            EnigmindError::DigitOutOfBounds(d) => write!(f, "DigitOutOfBounds({d})"),
+            EnigmindError::WrongCodeLength { expected, found } => {
+                write!(f, "WrongCodeLength(expected {expected}, found {found})")
+            }
+            EnigmindError::ConfigurationTooLarge => write!(f, "ConfigurationTooLarge"),
+            EnigmindError::InvalidConfiguration { base, column_count } => {
+                write!(
+                    f,
+                    "InvalidConfiguration(base {base}, column_count {column_count})"
+                )
+            }
+            EnigmindError::InvalidRange { low, high } => {
+                write!(f, "InvalidRange(low {low}, high {high})")
+            }
+            EnigmindError::NoRulesAvailable => write!(f, "NoRulesAvailable"),
+            EnigmindError::NoUniqueSolution => write!(f, "NoUniqueSolution"),
+            EnigmindError::CannotSatisfyConstraints => write!(f, "CannotSatisfyConstraints"),
+            EnigmindError::GenerationTimeout => write!(f, "GenerationTimeout"),
+            EnigmindError::UnsupportedSchemaVersion(v) => {
+                write!(f, "UnsupportedSchemaVersion({v})")
+            }
+            EnigmindError::ParseError(message) => write!(f, "ParseError({message})"),
+            EnigmindError::InvalidTestResult(message) => {
+                write!(f, "InvalidTestResult({message})")
+            }
         }
     }
 }
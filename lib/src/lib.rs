@@ -1,5 +1,7 @@
 #![deny(clippy::all)]
 
+#[cfg(feature = "binary")]
+pub mod binary;
 pub mod code;
 pub mod column;
 pub mod columns;
@@ -9,5 +11,10 @@ pub mod error;
 pub mod rule;
 pub mod rules;
 pub mod setup;
+#[cfg(feature = "generation")]
+pub mod simulation;
+pub mod solver;
 pub mod term_format;
 pub mod verifier;
+#[cfg(feature = "wasm")]
+pub mod wasm;
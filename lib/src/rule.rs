@@ -1,11 +1,21 @@
 use crate::{
-    code::Code, columns::ColumnSet, error::EnigmindError, rules::Rules, setup::GameConfiguration,
+    code::Code, column::Column, columns::ColumnSet, error::EnigmindError, rules::Rules,
+    setup::GameConfiguration,
 };
 use nbitmask::BitMask;
 use serde::{Deserialize, Serialize};
-use std::{fmt, vec};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashSet},
+    fmt,
+    str::FromStr,
+    vec,
+};
 
+/// Serialized with its name as tag, so adding or reordering variants keeps old games readable.
 #[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "op", content = "value")]
 pub enum Operator {
     Pair,
     Impair,
@@ -14,6 +24,49 @@ pub enum Operator {
     SumBelow(u8),
     SumEquals(u8),
     SumAbove(u8),
+    /// The sum divides by the argument, which should be at least 1
+    SumMultipleOf(u8),
+    /// Both bounds included, see `Operator::sum_between`
+    SumBetween(u8, u8),
+    ProductBelow(u8),
+    ProductEquals(u8),
+    ProductAbove(u8),
+    SameParity,
+    AlternatingParity,
+    /// Compares the highest digit of the whole code, whatever the columns
+    MaxEquals(u8),
+    /// Compares the lowest digit of the whole code, whatever the columns
+    MinEquals(u8),
+}
+
+/// Families of rules, for choosing which ones games are generated from.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum OperatorKind {
+    /// Even, odd, same and alternating parity
+    Parity,
+    /// Lowest and highest columns, highest and lowest digits of the code
+    Extremes,
+    Sums,
+    Products,
+    /// How many columns hold a digit, over the whole code or some columns
+    XColumnsEquals,
+    /// Digits of single columns, alone or compared to each other
+    Columns,
+    /// Distinct, repeated neighbouring and ordered digits of the whole code
+    Patterns,
+}
+
+impl OperatorKind {
+    pub const ALL: [OperatorKind; 7] = [
+        OperatorKind::Parity,
+        OperatorKind::Extremes,
+        OperatorKind::Sums,
+        OperatorKind::Products,
+        OperatorKind::XColumnsEquals,
+        OperatorKind::Columns,
+        OperatorKind::Patterns,
+    ];
 }
 
 impl fmt::Display for Operator {
@@ -26,23 +79,246 @@ impl fmt::Display for Operator {
             Operator::SumBelow(_) => write!(f, "below"),
             Operator::SumEquals(_) => write!(f, "equal to"),
             Operator::SumAbove(_) => write!(f, "above"),
+            Operator::SumMultipleOf(_) => write!(f, "a multiple of"),
+            Operator::SumBetween(..) => write!(f, "between"),
+            Operator::ProductBelow(_) => write!(f, "below"),
+            Operator::ProductEquals(_) => write!(f, "equal to"),
+            Operator::ProductAbove(_) => write!(f, "above"),
+            Operator::SameParity => write!(f, "of same parity"),
+            Operator::AlternatingParity => write!(f, "of alternating parity"),
+            Operator::MaxEquals(_) => write!(f, "maximum"),
+            Operator::MinEquals(_) => write!(f, "minimum"),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
+impl Operator {
+    pub fn sum_between(low: u8, high: u8) -> Result<Self, EnigmindError> {
+        match low <= high {
+            true => Ok(Operator::SumBetween(low, high)),
+            false => Err(EnigmindError::InvalidRange { low, high }),
+        }
+    }
+
+    pub fn kind(&self) -> OperatorKind {
+        match self {
+            Operator::Pair
+            | Operator::Impair
+            | Operator::SameParity
+            | Operator::AlternatingParity => OperatorKind::Parity,
+            Operator::Lowest
+            | Operator::Highest
+            | Operator::MaxEquals(_)
+            | Operator::MinEquals(_) => OperatorKind::Extremes,
+            Operator::SumBelow(_)
+            | Operator::SumEquals(_)
+            | Operator::SumAbove(_)
+            | Operator::SumMultipleOf(_)
+            | Operator::SumBetween(..) => OperatorKind::Sums,
+            Operator::ProductBelow(_) | Operator::ProductEquals(_) | Operator::ProductAbove(_) => {
+                OperatorKind::Products
+            }
+        }
+    }
+
+    /// Whether the operator only depends on the sum of its columns and how many are odd.
+    fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            Operator::Pair
+                | Operator::Impair
+                | Operator::SameParity
+                | Operator::SumBelow(_)
+                | Operator::SumEquals(_)
+                | Operator::SumAbove(_)
+                | Operator::SumMultipleOf(_)
+                | Operator::SumBetween(..)
+        )
+    }
+
+    /// Evaluates an arithmetic operator over `len` columns from their sum and odd digits.
+    fn accepts_sum(&self, sum: u8, odd: usize, len: usize) -> bool {
+        match self {
+            Operator::Pair => odd == 0,
+            Operator::Impair => odd == len,
+            Operator::SameParity => odd == 0 || odd == len,
+            Operator::SumBelow(value) => sum < *value,
+            Operator::SumEquals(value) => sum == *value,
+            Operator::SumAbove(value) => sum > *value,
+            Operator::SumMultipleOf(value) => sum.checked_rem(*value).unwrap_or(sum) == 0,
+            Operator::SumBetween(low, high) => (*low..=*high).contains(&sum),
+            _ => false,
+        }
+    }
+
+    /// Short form of the operator with its argument, e.g. "sum<7", read back by `try_from`.
+    pub fn keyword(&self) -> String {
+        match self {
+            Operator::SumBelow(value) => format!("sum<{value}"),
+            Operator::SumEquals(value) => format!("sum={value}"),
+            Operator::SumAbove(value) => format!("sum>{value}"),
+            Operator::SumMultipleOf(value) => format!("sum%{value}"),
+            Operator::SumBetween(low, high) => format!("sum={low}..{high}"),
+            Operator::ProductBelow(value) => format!("product<{value}"),
+            Operator::ProductEquals(value) => format!("product={value}"),
+            Operator::ProductAbove(value) => format!("product>{value}"),
+            Operator::SameParity => "same-parity".to_string(),
+            Operator::AlternatingParity => "alternating-parity".to_string(),
+            Operator::MaxEquals(value) => format!("max={value}"),
+            Operator::MinEquals(value) => format!("min={value}"),
+            _ => self.to_string(),
+        }
+    }
+
+    /// The operator with its argument, e.g. "sum below 7".
+    pub fn describe(&self) -> String {
+        match self {
+            Operator::SumBelow(value) | Operator::SumEquals(value) | Operator::SumAbove(value) => {
+                format!("sum {self} {value}")
+            }
+            Operator::SumMultipleOf(value) => format!("sum is {self} {value}"),
+            Operator::SumBetween(low, high) => format!("sum {self} {low} and {high}"),
+            Operator::ProductBelow(value)
+            | Operator::ProductEquals(value)
+            | Operator::ProductAbove(value) => format!("product {self} {value}"),
+            Operator::MaxEquals(value) | Operator::MinEquals(value) => format!("{self} {value}"),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for Operator {
+    type Error = EnigmindError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let error = || EnigmindError::ParseError(format!("unknown operator '{s}'"));
+
+        match s {
+            "even" => return Ok(Operator::Pair),
+            "odd" => return Ok(Operator::Impair),
+            "lowest" => return Ok(Operator::Lowest),
+            "highest" => return Ok(Operator::Highest),
+            "same-parity" => return Ok(Operator::SameParity),
+            "alternating-parity" => return Ok(Operator::AlternatingParity),
+            _ => (),
+        }
 
+        let at = s.find(['<', '=', '>', '%']).ok_or_else(error)?;
+        let (name, rest) = s.split_at(at);
+        let (symbol, value) = rest.split_at(1);
+        let parse = |value: &str| value.parse::<u8>().map_err(|_| error());
+
+        if let ("sum", "=", Some((low, high))) = (name, symbol, value.split_once("..")) {
+            return Operator::sum_between(parse(low)?, parse(high)?);
+        }
+        let value = parse(value)?;
+        match (name, symbol) {
+            ("sum", "<") => Ok(Operator::SumBelow(value)),
+            ("sum", "=") => Ok(Operator::SumEquals(value)),
+            ("sum", ">") => Ok(Operator::SumAbove(value)),
+            ("sum", "%") => Ok(Operator::SumMultipleOf(value)),
+            ("product", "<") => Ok(Operator::ProductBelow(value)),
+            ("product", "=") => Ok(Operator::ProductEquals(value)),
+            ("product", ">") => Ok(Operator::ProductAbove(value)),
+            ("max", "=") => Ok(Operator::MaxEquals(value)),
+            ("min", "=") => Ok(Operator::MinEquals(value)),
+            _ => Err(error()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(remote = "Ordering")]
+enum OrderingDef {
+    Less,
+    Equal,
+    Greater,
+}
+
+/// Serialized with its name as tag, like `Operator`.
+#[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "rule", content = "args")]
 pub enum Rule {
     MatchesOp(Operator, ColumnSet),
     XColumnsEquals(u8, u8),
+    /// Number of the given columns holding `value`
+    SetColumnsEqual {
+        columns: ColumnSet,
+        value: u8,
+        count: u8,
+    },
+    ColumnEquals(Column, u8),
+    ColumnCompare {
+        left: Column,
+        right: Column,
+        #[serde(with = "OrderingDef")]
+        ordering: Ordering,
+    },
+    DistinctValues(u8),
+    /// Number of pairs of neighbouring columns holding the same digit, over the whole code
+    AdjacentEquals(u8),
+    /// Ordering of the digits of the whole code, read from the first column
+    Monotonic {
+        strictly: bool,
+        ascending: bool,
+    },
+    Not(Box<Rule>),
+    And(Box<Rule>, Box<Rule>),
+    Or(Box<Rule>, Box<Rule>),
 }
 
 impl Rule {
+    pub fn negate(self) -> Rule {
+        match self {
+            Rule::Not(rule) => *rule,
+            rule => Rule::Not(Box::new(rule)),
+        }
+    }
+
     pub fn evaluate(&self, code: Code) -> Result<bool, EnigmindError> {
         Ok(match self {
+            Rule::Not(rule) => !rule.evaluate(code)?,
+            Rule::And(left, right) => left.evaluate(code.clone())? && right.evaluate(code)?,
+            Rule::Or(left, right) => left.evaluate(code.clone())? || right.evaluate(code)?,
             Rule::XColumnsEquals(count, value) => {
                 code.0.iter().filter(|x| **x == *value).count() == (*count as usize)
             }
+            Rule::SetColumnsEqual {
+                columns,
+                value,
+                count,
+            } => {
+                let mut equal = 0;
+                for col in columns.iter() {
+                    if code.get(*col)? == *value {
+                        equal += 1;
+                    }
+                }
+                equal == *count
+            }
+            Rule::ColumnEquals(column, value) => code.get(*column)? == *value,
+            Rule::ColumnCompare {
+                left,
+                right,
+                ordering,
+            } => code.get(*left)?.cmp(&code.get(*right)?) == *ordering,
+            Rule::DistinctValues(count) => {
+                code.0.iter().collect::<HashSet<_>>().len() == *count as usize
+            }
+            Rule::AdjacentEquals(count) => {
+                code.0.windows(2).filter(|w| w[0] == w[1]).count() == *count as usize
+            }
+            Rule::Monotonic {
+                strictly,
+                ascending,
+            } => code.0.windows(2).all(|w| match (strictly, ascending) {
+                (true, true) => w[0] < w[1],
+                (false, true) => w[0] <= w[1],
+                (true, false) => w[0] > w[1],
+                (false, false) => w[0] >= w[1],
+            }),
             Rule::MatchesOp(op, columns) => match op {
                 Operator::Highest => {
                     let mut res = true;
@@ -106,22 +382,201 @@ impl Rule {
                     }
                     sum > *value
                 }
+                Operator::SumMultipleOf(value) => {
+                    let mut sum = 0;
+                    for col in columns.iter() {
+                        sum += code.get(*col)?;
+                    }
+                    // Only 0 is a multiple of 0
+                    sum.checked_rem(*value).unwrap_or(sum) == 0
+                }
+                Operator::SumBetween(low, high) => {
+                    let mut sum = 0;
+                    for col in columns.iter() {
+                        sum += code.get(*col)?;
+                    }
+                    (*low..=*high).contains(&sum)
+                }
+                Operator::ProductBelow(value) => {
+                    let mut product = 1u32;
+                    for col in columns.iter() {
+                        product *= code.get(*col)? as u32;
+                    }
+                    product < *value as u32
+                }
+                Operator::ProductEquals(value) => {
+                    let mut product = 1u32;
+                    for col in columns.iter() {
+                        product *= code.get(*col)? as u32;
+                    }
+                    product == *value as u32
+                }
+                Operator::ProductAbove(value) => {
+                    let mut product = 1u32;
+                    for col in columns.iter() {
+                        product *= code.get(*col)? as u32;
+                    }
+                    product > *value as u32
+                }
+                Operator::SameParity => {
+                    let mut parities = Vec::new();
+                    for col in columns.sorted() {
+                        parities.push(code.get(col)? % 2);
+                    }
+                    parities.windows(2).all(|w| w[0] == w[1])
+                }
+                Operator::AlternatingParity => {
+                    let mut parities = Vec::new();
+                    for col in columns.sorted() {
+                        parities.push(code.get(col)? % 2);
+                    }
+                    parities.windows(2).all(|w| w[0] != w[1])
+                }
+                Operator::MaxEquals(value) => code.0.iter().max() == Some(value),
+                Operator::MinEquals(value) => code.0.iter().min() == Some(value),
             },
         })
     }
 
+    /// Families of the rule, those of both sides for combined rules.
+    pub fn kinds(&self) -> BTreeSet<OperatorKind> {
+        match self {
+            Rule::Not(rule) => rule.kinds(),
+            Rule::And(left, right) | Rule::Or(left, right) => {
+                left.kinds().union(&right.kinds()).copied().collect()
+            }
+            Rule::MatchesOp(op, _) => BTreeSet::from([op.kind()]),
+            Rule::XColumnsEquals(..) | Rule::SetColumnsEqual { .. } => {
+                BTreeSet::from([OperatorKind::XColumnsEquals])
+            }
+            Rule::ColumnEquals(..) | Rule::ColumnCompare { .. } => {
+                BTreeSet::from([OperatorKind::Columns])
+            }
+            Rule::DistinctValues(_) | Rule::AdjacentEquals(_) | Rule::Monotonic { .. } => {
+                BTreeSet::from([OperatorKind::Patterns])
+            }
+        }
+    }
+
     pub fn get_mask(&self, config: &GameConfiguration) -> Result<BitMask<u64>, EnigmindError> {
-        let n = config.solution_count() as usize;
-        let mut mask = BitMask::zeros(n);
+        match self {
+            Rule::And(left, right) => Ok(&left.get_mask(config)? & &right.get_mask(config)?),
+            Rule::Or(left, right) => Ok(&left.get_mask(config)? | &right.get_mask(config)?),
+            Rule::MatchesOp(op, columns) if op.is_arithmetic() => {
+                arithmetic_mask(op, columns, config)
+            }
+            _ => self.evaluated_mask(config),
+        }
+    }
+
+    /// Mask built by evaluating the rule on every code, whatever the rule.
+    fn evaluated_mask(&self, config: &GameConfiguration) -> Result<BitMask<u64>, EnigmindError> {
+        let mut mask = BitMask::zeros(config.solution_count() as usize);
 
-        for i in 0..n {
-            let code = Code::from_shift(i as u32, config);
+        for (i, code) in config.codes().enumerate() {
             mask.set(i, self.evaluate(code)?)?;
         }
 
         Ok(mask)
     }
 
+    /// Why the rule holds or not for a code, from the digits it looks at.
+    pub fn explain(&self, code: &Code) -> Result<String, EnigmindError> {
+        let verdict = match self.evaluate(code.clone())? {
+            true => "holds",
+            false => "does not hold",
+        };
+        Ok(format!("{}, so {self} {verdict}", self.facts(code)?))
+    }
+
+    /// Digits the rule looks at, without any verdict so that composed rules
+    /// only conclude once.
+    fn facts(&self, code: &Code) -> Result<String, EnigmindError> {
+        let values = |columns: &ColumnSet| -> Result<String, EnigmindError> {
+            let mut values = Vec::new();
+            for column in columns.sorted() {
+                values.push(format!("{column} is {}", code.get(column)?));
+            }
+            Ok(values.join(", "))
+        };
+
+        let facts = match self {
+            Rule::Not(rule) => rule.facts(code)?,
+            Rule::And(left, right) | Rule::Or(left, right) => {
+                format!("{}; {}", left.facts(code)?, right.facts(code)?)
+            }
+            Rule::XColumnsEquals(_, value) => format!(
+                "{} column(s) equal {value}",
+                code.0.iter().filter(|x| **x == *value).count()
+            ),
+            Rule::SetColumnsEqual { columns, .. } => values(columns)?,
+            Rule::ColumnEquals(column, _) => format!("{column} is {}", code.get(*column)?),
+            Rule::ColumnCompare { left, right, .. } => format!(
+                "{left} is {} and {right} is {}",
+                code.get(*left)?,
+                code.get(*right)?
+            ),
+            Rule::DistinctValues(_) => format!(
+                "the code uses {} distinct value(s)",
+                code.0.iter().collect::<HashSet<_>>().len()
+            ),
+            Rule::AdjacentEquals(_) => format!(
+                "there are {} pair(s) of adjacent equal columns",
+                code.0.windows(2).filter(|w| w[0] == w[1]).count()
+            ),
+            Rule::Monotonic { .. } => format!("the digits read {code}"),
+            Rule::MatchesOp(op, columns) => match op {
+                Operator::Pair
+                | Operator::Impair
+                | Operator::SameParity
+                | Operator::AlternatingParity => values(columns)?,
+                Operator::Lowest | Operator::Highest => {
+                    let extreme = match op {
+                        Operator::Lowest => code.0.iter().min(),
+                        _ => code.0.iter().max(),
+                    }
+                    .copied()
+                    .ok_or(EnigmindError::EmptyCode)?;
+                    format!(
+                        "{}, and the {op} digit is {extreme}, found in {} column(s)",
+                        values(columns)?,
+                        code.0.iter().filter(|x| **x == extreme).count()
+                    )
+                }
+                Operator::SumBelow(_)
+                | Operator::SumEquals(_)
+                | Operator::SumAbove(_)
+                | Operator::SumMultipleOf(_)
+                | Operator::SumBetween(..) => {
+                    let mut sum = 0;
+                    for column in columns.iter() {
+                        sum += code.get(*column)? as u32;
+                    }
+                    format!("the sum of columns {columns} is {sum}")
+                }
+                Operator::ProductBelow(_)
+                | Operator::ProductEquals(_)
+                | Operator::ProductAbove(_) => {
+                    let mut product = 1u32;
+                    for column in columns.iter() {
+                        product *= code.get(*column)? as u32;
+                    }
+                    format!("the product of columns {columns} is {product}")
+                }
+                Operator::MaxEquals(_) => format!(
+                    "the highest digit is {}",
+                    code.0.iter().max().ok_or(EnigmindError::EmptyCode)?
+                ),
+                Operator::MinEquals(_) => format!(
+                    "the lowest digit is {}",
+                    code.0.iter().min().ok_or(EnigmindError::EmptyCode)?
+                ),
+            },
+        };
+
+        Ok(facts)
+    }
+
     pub fn get_similar(&self, gc: &GameConfiguration) -> Vec<(String, Rules)> {
         let mut v = Vec::new();
 
@@ -176,7 +631,99 @@ impl Rule {
                     ));
 
                     v.push((
-                        format!("Sum of {} columns is {} {}", columns.len(), *op, *value),
+                        format!("{} columns have a {}", columns.len(), op.describe()),
+                        gc.get_column_combinations(columns.len() as u8)
+                            .iter()
+                            .map(|cs| Rule::MatchesOp(*op, cs.clone()))
+                            .collect(),
+                    ));
+                }
+                Operator::SumMultipleOf(value) => {
+                    v.push((
+                        format!("Column(s) {columns} sum is a multiple of X"),
+                        (2..=gc.base)
+                            .map(|value| {
+                                Rule::MatchesOp(Operator::SumMultipleOf(value), columns.clone())
+                            })
+                            .collect(),
+                    ));
+
+                    v.push((
+                        format!("The sum of {} columns is {op} {value}", columns.len()),
+                        gc.get_column_combinations(columns.len() as u8)
+                            .iter()
+                            .map(|cs| Rule::MatchesOp(*op, cs.clone()))
+                            .collect(),
+                    ));
+                }
+                Operator::SumBetween(low, high) => {
+                    v.push((
+                        format!(
+                            "Column(s) {columns} sum is below, between or above {low} and {high}"
+                        ),
+                        vec![
+                            Rule::MatchesOp(Operator::SumBelow(*low), columns.clone()),
+                            Rule::MatchesOp(*op, columns.clone()),
+                            Rule::MatchesOp(Operator::SumAbove(*high), columns.clone()),
+                        ]
+                        .into(),
+                    ));
+
+                    v.push((
+                        format!("{} columns have a {}", columns.len(), op.describe()),
+                        gc.get_column_combinations(columns.len() as u8)
+                            .iter()
+                            .map(|cs| Rule::MatchesOp(*op, cs.clone()))
+                            .collect(),
+                    ));
+                }
+                Operator::SameParity | Operator::AlternatingParity => {
+                    v.push((
+                        format!("Columns {columns} have the same or alternating parity"),
+                        vec![
+                            Rule::MatchesOp(Operator::SameParity, columns.clone()),
+                            Rule::MatchesOp(Operator::AlternatingParity, columns.clone()),
+                        ]
+                        .into(),
+                    ));
+
+                    v.push((
+                        format!("{} columns are {}", columns.len(), *op),
+                        gc.get_column_combinations(columns.len() as u8)
+                            .iter()
+                            .map(|cs| Rule::MatchesOp(*op, cs.clone()))
+                            .collect(),
+                    ));
+                }
+                Operator::MaxEquals(_) => v.push((
+                    "The highest value of the code is X".to_string(),
+                    (0..gc.base)
+                        .map(|value| Rule::MatchesOp(Operator::MaxEquals(value), columns.clone()))
+                        .collect(),
+                )),
+                Operator::MinEquals(_) => v.push((
+                    "The lowest value of the code is X".to_string(),
+                    (0..gc.base)
+                        .map(|value| Rule::MatchesOp(Operator::MinEquals(value), columns.clone()))
+                        .collect(),
+                )),
+                Operator::ProductBelow(value)
+                | Operator::ProductEquals(value)
+                | Operator::ProductAbove(value) => {
+                    v.push((
+                        format!(
+                            "The product of columns {columns} is below, equal to or above {value}"
+                        ),
+                        vec![
+                            Rule::MatchesOp(Operator::ProductBelow(*value), columns.clone()),
+                            Rule::MatchesOp(Operator::ProductEquals(*value), columns.clone()),
+                            Rule::MatchesOp(Operator::ProductAbove(*value), columns.clone()),
+                        ]
+                        .into(),
+                    ));
+
+                    v.push((
+                        format!("The product of {} columns is {op} {value}", columns.len()),
                         gc.get_column_combinations(columns.len() as u8)
                             .iter()
                             .map(|cs| Rule::MatchesOp(*op, cs.clone()))
@@ -195,6 +742,79 @@ impl Rule {
                     equal_rules.into(),
                 ));
             }
+            Rule::SetColumnsEqual { columns, value, .. } => {
+                v.push((
+                    format!("X of columns {columns} equal {value}"),
+                    (0..=columns.len() as u8)
+                        .map(|count| Rule::SetColumnsEqual {
+                            columns: columns.clone(),
+                            value: *value,
+                            count,
+                        })
+                        .collect(),
+                ));
+            }
+            Rule::ColumnEquals(column, _) => {
+                v.push((
+                    format!("Value of column {column}"),
+                    (0..gc.base)
+                        .map(|value| Rule::ColumnEquals(*column, value))
+                        .collect(),
+                ));
+            }
+            Rule::ColumnCompare { left, right, .. } => {
+                v.push((
+                    format!("Comparison between column {left} and column {right}"),
+                    [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                        .into_iter()
+                        .map(|ordering| Rule::ColumnCompare {
+                            left: *left,
+                            right: *right,
+                            ordering,
+                        })
+                        .collect(),
+                ));
+            }
+            Rule::DistinctValues(_) => {
+                v.push((
+                    "The code uses X distinct values".to_string(),
+                    (1..=gc.column_count).map(Rule::DistinctValues).collect(),
+                ));
+            }
+            Rule::AdjacentEquals(_) => {
+                v.push((
+                    "There are X pairs of adjacent equal columns".to_string(),
+                    (0..gc.column_count).map(Rule::AdjacentEquals).collect(),
+                ));
+            }
+            Rule::Monotonic { .. } => {
+                v.push((
+                    "The digits are in ascending or descending order".to_string(),
+                    [(false, true), (true, true), (false, false), (true, false)]
+                        .into_iter()
+                        .map(|(strictly, ascending)| Rule::Monotonic {
+                            strictly,
+                            ascending,
+                        })
+                        .collect(),
+                ));
+            }
+            Rule::Not(rule) => {
+                v.push((
+                    format!("{rule} holds or not"),
+                    vec![(**rule).clone(), self.clone()].into(),
+                ));
+            }
+            Rule::And(left, right) | Rule::Or(left, right) => {
+                v.push((
+                    format!("{left} and/or {right}"),
+                    vec![
+                        Rule::And(left.clone(), right.clone()),
+                        Rule::Or(left.clone(), right.clone()),
+                    ]
+                    .into(),
+                ));
+            }
         }
         v
     }
@@ -204,6 +824,35 @@ impl fmt::Display for Rule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text = match self {
             Rule::XColumnsEquals(count, value) => format!("XColumnsEquals({count}, {value})"),
+            Rule::SetColumnsEqual {
+                columns,
+                value,
+                count,
+            } => format!("SetColumnsEqual({columns}, {value}, {count})"),
+            Rule::ColumnEquals(column, value) => format!("{column} == {value}"),
+            Rule::ColumnCompare {
+                left,
+                right,
+                ordering,
+            } => match ordering {
+                Ordering::Less => format!("{left} < {right}"),
+                Ordering::Equal => format!("{left} = {right}"),
+                Ordering::Greater => format!("{left} > {right}"),
+            },
+            Rule::DistinctValues(count) => format!("DistinctValues({count})"),
+            Rule::AdjacentEquals(count) => format!("AdjacentEquals({count})"),
+            Rule::Monotonic {
+                strictly,
+                ascending,
+            } => match (strictly, ascending) {
+                (false, true) => "Ascending".to_string(),
+                (true, true) => "Strictly ascending".to_string(),
+                (false, false) => "Descending".to_string(),
+                (true, false) => "Strictly descending".to_string(),
+            },
+            Rule::Not(rule) => format!("NOT({rule})"),
+            Rule::And(left, right) => format!("AND({left}, {right})"),
+            Rule::Or(left, right) => format!("OR({left}, {right})"),
 
             Rule::MatchesOp(op, columns) => match op {
                 Operator::Lowest => format!("IsLowest({columns})"),
@@ -213,9 +862,828 @@ impl fmt::Display for Rule {
                 Operator::SumBelow(value) => format!("SumBelow({columns}, {value})"),
                 Operator::SumEquals(value) => format!("SumEquals({columns}, {value})"),
                 Operator::SumAbove(value) => format!("SumAbove({columns}, {value})"),
+                Operator::SumMultipleOf(value) => format!("SumMultipleOf({columns}, {value})"),
+                Operator::SumBetween(low, high) => {
+                    format!("SumBetween({columns}, {low}, {high})")
+                }
+                Operator::ProductBelow(value) => format!("ProductBelow({columns}, {value})"),
+                Operator::ProductEquals(value) => format!("ProductEquals({columns}, {value})"),
+                Operator::ProductAbove(value) => format!("ProductAbove({columns}, {value})"),
+                Operator::SameParity => format!("SameParity({columns})"),
+                Operator::AlternatingParity => format!("AlternatingParity({columns})"),
+                Operator::MaxEquals(value) => format!("MaxEquals({value})"),
+                Operator::MinEquals(value) => format!("MinEquals({value})"),
             },
         };
 
         write!(f, "{text}")
     }
 }
+
+/// Splits arguments on the commas outside of any brackets or parentheses.
+fn split_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(args[start..].trim());
+    parts
+}
+
+/// Reads back a rule as displayed. Extreme values don't show their columns, which they
+/// ignore, so they are read with no columns at all.
+impl FromStr for Rule {
+    type Err = EnigmindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let error = || EnigmindError::ParseError(format!("unknown rule '{s}'"));
+        let number = |arg: &str| arg.parse::<u8>().map_err(|_| error());
+        let rule = |arg: &str| arg.parse::<Rule>().map(Box::new);
+
+        let monotonic = match s {
+            "Ascending" => Some((false, true)),
+            "Strictly ascending" => Some((true, true)),
+            "Descending" => Some((false, false)),
+            "Strictly descending" => Some((true, false)),
+            _ => None,
+        };
+        if let Some((strictly, ascending)) = monotonic {
+            return Ok(Rule::Monotonic {
+                strictly,
+                ascending,
+            });
+        }
+
+        let Some((name, args)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) else {
+            // Column comparisons, e.g. "A == 3" or "A < B"
+            return match s.split(' ').collect::<Vec<_>>()[..] {
+                [column, "==", value] => Ok(Rule::ColumnEquals(column.parse()?, number(value)?)),
+                [left, ordering, right] => Ok(Rule::ColumnCompare {
+                    left: left.parse()?,
+                    right: right.parse()?,
+                    ordering: match ordering {
+                        "<" => Ordering::Less,
+                        "=" => Ordering::Equal,
+                        ">" => Ordering::Greater,
+                        _ => return Err(error()),
+                    },
+                }),
+                _ => Err(error()),
+            };
+        };
+
+        let op = |op: Operator, columns: &str| Ok(Rule::MatchesOp(op, columns.parse()?));
+        match (name, &split_args(args)[..]) {
+            ("NOT", [inner]) => Ok(Rule::Not(rule(inner)?)),
+            ("AND", [left, right]) => Ok(Rule::And(rule(left)?, rule(right)?)),
+            ("OR", [left, right]) => Ok(Rule::Or(rule(left)?, rule(right)?)),
+            ("XColumnsEquals", [count, value]) => {
+                Ok(Rule::XColumnsEquals(number(count)?, number(value)?))
+            }
+            ("SetColumnsEqual", [columns, value, count]) => Ok(Rule::SetColumnsEqual {
+                columns: columns.parse()?,
+                value: number(value)?,
+                count: number(count)?,
+            }),
+            ("DistinctValues", [count]) => Ok(Rule::DistinctValues(number(count)?)),
+            ("AdjacentEquals", [count]) => Ok(Rule::AdjacentEquals(number(count)?)),
+            ("IsLowest", [columns]) => op(Operator::Lowest, columns),
+            ("IsHighest", [columns]) => op(Operator::Highest, columns),
+            ("IsPair", [columns]) => op(Operator::Pair, columns),
+            ("IsImpair", [columns]) => op(Operator::Impair, columns),
+            ("SumBelow", [columns, value]) => op(Operator::SumBelow(number(value)?), columns),
+            ("SumEquals", [columns, value]) => op(Operator::SumEquals(number(value)?), columns),
+            ("SumAbove", [columns, value]) => op(Operator::SumAbove(number(value)?), columns),
+            ("SumMultipleOf", [columns, value]) => {
+                op(Operator::SumMultipleOf(number(value)?), columns)
+            }
+            ("SumBetween", [columns, low, high]) => {
+                op(Operator::sum_between(number(low)?, number(high)?)?, columns)
+            }
+            ("ProductBelow", [columns, value]) => {
+                op(Operator::ProductBelow(number(value)?), columns)
+            }
+            ("ProductEquals", [columns, value]) => {
+                op(Operator::ProductEquals(number(value)?), columns)
+            }
+            ("ProductAbove", [columns, value]) => {
+                op(Operator::ProductAbove(number(value)?), columns)
+            }
+            ("SameParity", [columns]) => op(Operator::SameParity, columns),
+            ("AlternatingParity", [columns]) => op(Operator::AlternatingParity, columns),
+            ("MaxEquals", [value]) => op(Operator::MaxEquals(number(value)?), "[]"),
+            ("MinEquals", [value]) => op(Operator::MinEquals(number(value)?), "[]"),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Mask of an arithmetic operator, codes being enumerated in shift order like an odometer so
+/// the sum and odd digit count only change for the digits that did.
+fn arithmetic_mask(
+    op: &Operator,
+    columns: &ColumnSet,
+    config: &GameConfiguration,
+) -> Result<BitMask<u64>, EnigmindError> {
+    let column_count = config.column_count as usize;
+    let mut selected = vec![false; column_count];
+    for column in columns.iter() {
+        let index: usize = (*column).into();
+        *selected
+            .get_mut(index)
+            .ok_or(EnigmindError::ColumnIndexOutOfBounds)? = true;
+    }
+    let len = columns.len();
+
+    let mut mask = BitMask::zeros(config.solution_count() as usize);
+    let mut digits = vec![0u8; column_count];
+    let mut sum = 0u8;
+    let mut odd = 0usize;
+    for i in 0..config.solution_count() as usize {
+        if op.accepts_sum(sum, odd, len) {
+            mask.set(i, true)?;
+        }
+
+        // The last digit goes up, carrying over to the previous ones
+        for (digit, selected) in digits.iter_mut().zip(&selected).rev() {
+            let old = *digit;
+            *digit = (old + 1) % config.base;
+            if *selected {
+                sum = sum - old + *digit;
+                odd = odd - (old % 2) as usize + (*digit % 2) as usize;
+            }
+            if *digit != 0 {
+                break;
+            }
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Operator, Rule};
+    use crate::{
+        code::Code, column::Column, columns::ColumnSet, error::EnigmindError,
+        setup::test_configuration,
+    };
+    use std::{cmp::Ordering, collections::HashSet};
+
+    fn columns(indexes: &[u8]) -> ColumnSet {
+        HashSet::from_iter(indexes.iter().map(|i| Column::from(*i))).into()
+    }
+
+    #[test]
+    fn test_operator_describe() {
+        assert_eq!(Operator::SumBelow(7).describe(), "sum below 7");
+        assert!(Operator::ProductEquals(12).describe().contains("12"));
+        assert_eq!(Operator::MaxEquals(3).describe(), "maximum 3");
+        assert_eq!(Operator::Pair.describe(), Operator::Pair.to_string());
+    }
+
+    #[test]
+    fn test_product_equals() {
+        let rule = Rule::MatchesOp(Operator::ProductEquals(6), columns(&[0, 2]));
+
+        assert!(rule.evaluate(Code::new(vec![2, 0, 3])).unwrap());
+        assert!(rule.evaluate(Code::new(vec![3, 4, 2])).unwrap());
+        assert!(!rule.evaluate(Code::new(vec![2, 3, 0])).unwrap());
+    }
+
+    #[test]
+    fn test_product_masks() {
+        let gc = test_configuration(2, 3);
+        let count = |op| {
+            Rule::MatchesOp(op, columns(&[0, 1]))
+                .get_mask(&gc)
+                .unwrap()
+                .count_ones()
+        };
+
+        assert_eq!(count(Operator::ProductEquals(0)), 5);
+        assert_eq!(count(Operator::ProductEquals(2)), 2);
+        assert_eq!(count(Operator::ProductBelow(2)), 6);
+        assert_eq!(count(Operator::ProductAbove(1)), 3);
+
+        let similar =
+            Rule::MatchesOp(Operator::ProductBelow(12), columns(&[0, 1])).get_similar(&gc);
+        assert_eq!(
+            similar[0].0,
+            "The product of columns [A, B] is below, equal to or above 12"
+        );
+        assert_eq!(similar[1].0, "The product of 2 columns is below 12");
+    }
+
+    #[test]
+    fn test_sum_multiple_of() {
+        let gc = test_configuration(2, 5);
+        let count = |modulus| {
+            Rule::MatchesOp(Operator::SumMultipleOf(modulus), columns(&[0, 1]))
+                .get_mask(&gc)
+                .unwrap()
+                .count_ones()
+        };
+
+        assert_eq!(count(3), 8);
+        assert_eq!(count(2), 13);
+        assert_eq!(count(1), 25);
+        assert_eq!(count(0), 1);
+        assert_eq!(
+            Operator::SumMultipleOf(3).describe(),
+            "sum is a multiple of 3"
+        );
+
+        let similar =
+            Rule::MatchesOp(Operator::SumMultipleOf(3), columns(&[0, 1])).get_similar(&gc);
+        assert_eq!(similar[1].0, "The sum of 2 columns is a multiple of 3");
+    }
+
+    #[test]
+    fn test_sum_between() {
+        let gc = test_configuration(3, 5);
+        let mask = |op| Rule::MatchesOp(op, columns(&[0, 2])).get_mask(&gc).unwrap();
+
+        let between = mask(Operator::sum_between(3, 5).unwrap());
+        assert!(between == &mask(Operator::SumAbove(2)) & &mask(Operator::SumBelow(6)));
+        assert!(between.count_ones() > 0);
+
+        assert!(mask(Operator::sum_between(4, 4).unwrap()) == mask(Operator::SumEquals(4)));
+        assert!(Operator::sum_between(5, 3).is_err());
+        assert_eq!(Operator::SumBetween(5, 9).describe(), "sum between 5 and 9");
+    }
+
+    #[test]
+    fn test_set_columns_equal() {
+        let gc = test_configuration(3, 3);
+        let rule = |count| Rule::SetColumnsEqual {
+            columns: columns(&[0, 2]),
+            value: 0,
+            count,
+        };
+        let count = |count| rule(count).get_mask(&gc).unwrap().count_ones();
+
+        assert_eq!(count(0), 12);
+        assert_eq!(count(1), 12);
+        assert_eq!(count(2), 3);
+
+        assert!(rule(1).evaluate(Code::new(vec![0, 0, 2])).unwrap());
+        assert!(rule(2).evaluate(Code::new(vec![0, 1, 0])).unwrap());
+        assert!(rule(0).evaluate(Code::new(vec![1, 0, 2])).unwrap());
+        assert_eq!(rule(1).get_similar(&gc)[0].1.len(), 3);
+    }
+
+    #[test]
+    fn test_column_compare() {
+        let gc = test_configuration(3, 5);
+        let compare = |ordering| Rule::ColumnCompare {
+            left: Column::from(0),
+            right: Column::from(1),
+            ordering,
+        };
+
+        let code = Code::new(vec![3, 1, 4]);
+        assert!(compare(Ordering::Greater).evaluate(code.clone()).unwrap());
+        assert!(!compare(Ordering::Equal).evaluate(code.clone()).unwrap());
+        assert!(!compare(Ordering::Less).evaluate(code).unwrap());
+
+        assert_eq!(
+            compare(Ordering::Less).get_mask(&gc).unwrap().count_ones(),
+            50
+        );
+        assert_eq!(
+            compare(Ordering::Equal).get_mask(&gc).unwrap().count_ones(),
+            25
+        );
+        assert_eq!(
+            compare(Ordering::Greater)
+                .get_mask(&gc)
+                .unwrap()
+                .count_ones(),
+            50
+        );
+        assert_eq!(compare(Ordering::Greater).to_string(), "A > B");
+    }
+
+    #[test]
+    fn test_parity_across_columns() {
+        let same = Rule::MatchesOp(Operator::SameParity, columns(&[0, 1, 2]));
+        let alternating = Rule::MatchesOp(Operator::AlternatingParity, columns(&[2, 0, 1]));
+
+        assert!(same.evaluate(Code::new(vec![2, 4, 0])).unwrap());
+        assert!(same.evaluate(Code::new(vec![1, 3, 3])).unwrap());
+        assert!(!alternating.evaluate(Code::new(vec![2, 4, 0])).unwrap());
+
+        assert!(alternating.evaluate(Code::new(vec![2, 1, 4])).unwrap());
+        assert!(alternating.evaluate(Code::new(vec![1, 0, 3])).unwrap());
+        assert!(!same.evaluate(Code::new(vec![2, 1, 4])).unwrap());
+
+        assert!(!same.evaluate(Code::new(vec![2, 2, 1])).unwrap());
+        assert!(!alternating.evaluate(Code::new(vec![2, 2, 1])).unwrap());
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let gc = test_configuration(3, 3);
+        let count = |n| Rule::DistinctValues(n).get_mask(&gc).unwrap().count_ones();
+
+        assert!(Rule::DistinctValues(2)
+            .evaluate(Code::new(vec![1, 0, 1]))
+            .unwrap());
+        assert_eq!(count(1), 3);
+        assert_eq!(count(2), 18);
+        assert_eq!(count(3), 6);
+    }
+
+    #[test]
+    fn test_adjacent_equals() {
+        let gc = test_configuration(3, 5);
+
+        assert!(Rule::AdjacentEquals(1)
+            .evaluate(Code::new(vec![1, 1, 2]))
+            .unwrap());
+        assert!(Rule::AdjacentEquals(2)
+            .evaluate(Code::new(vec![1, 1, 1]))
+            .unwrap());
+        assert!(Rule::AdjacentEquals(0)
+            .evaluate(Code::new(vec![1, 2, 1]))
+            .unwrap());
+        assert_eq!(
+            Rule::AdjacentEquals(2).get_mask(&gc).unwrap().count_ones(),
+            5
+        );
+
+        let single_column = test_configuration(1, 5);
+        let similar = Rule::AdjacentEquals(0).get_similar(&single_column);
+        assert_eq!(similar[0].1.len(), 1);
+        assert_eq!(
+            Rule::AdjacentEquals(0)
+                .get_mask(&single_column)
+                .unwrap()
+                .count_ones(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_max_min_equals() {
+        let gc = test_configuration(2, 5);
+        let count = |op| {
+            Rule::MatchesOp(op, columns(&[0, 1]))
+                .get_mask(&gc)
+                .unwrap()
+                .count_ones()
+        };
+
+        assert_eq!(count(Operator::MaxEquals(4)), 9);
+        assert_eq!(count(Operator::MaxEquals(0)), 1);
+        assert_eq!(count(Operator::MinEquals(0)), 9);
+        assert_eq!(count(Operator::MinEquals(4)), 1);
+
+        // Only the first column is selected, but the whole code is considered
+        let max = Rule::MatchesOp(Operator::MaxEquals(3), columns(&[0]));
+        assert!(max.evaluate(Code::new(vec![1, 3])).unwrap());
+    }
+
+    #[test]
+    fn test_not() {
+        let gc = test_configuration(3, 3);
+        let pair = Rule::MatchesOp(Operator::Pair, columns(&[0]));
+        let not_pair = pair.clone().negate();
+
+        assert_eq!(
+            not_pair.get_mask(&gc).unwrap().count_ones(),
+            gc.solution_count() as usize - pair.get_mask(&gc).unwrap().count_ones()
+        );
+        assert!(not_pair.evaluate(Code::new(vec![1, 0, 0])).unwrap());
+        assert!(!not_pair.evaluate(Code::new(vec![2, 0, 0])).unwrap());
+        assert_eq!(not_pair.to_string(), "NOT(IsPair([A]))");
+        assert!(not_pair.negate() == pair);
+    }
+
+    #[test]
+    fn test_and_or() {
+        let gc = test_configuration(3, 5);
+        let sum = Rule::MatchesOp(Operator::SumAbove(5), columns(&[0, 1, 2]));
+        let pair = Rule::MatchesOp(Operator::Pair, columns(&[0]));
+        let sum_mask = sum.get_mask(&gc).unwrap();
+        let pair_mask = pair.get_mask(&gc).unwrap();
+
+        let and = Rule::And(Box::new(sum.clone()), Box::new(pair.clone()));
+        let or = Rule::Or(Box::new(sum), Box::new(pair));
+
+        assert!(and.get_mask(&gc).unwrap() == &sum_mask & &pair_mask);
+        assert!(or.get_mask(&gc).unwrap() == &sum_mask | &pair_mask);
+
+        let json = serde_json::to_string(&and).unwrap();
+        assert!(serde_json::from_str::<Rule>(&json).unwrap() == and);
+
+        assert!(and.evaluate(Code::new(vec![4, 1, 1])).unwrap());
+        assert!(!and.evaluate(Code::new(vec![3, 2, 1])).unwrap());
+        assert!(or.evaluate(Code::new(vec![3, 2, 1])).unwrap());
+        assert!(!or.evaluate(Code::new(vec![1, 1, 1])).unwrap());
+    }
+
+    #[test]
+    fn test_monotonic() {
+        let rule = |strictly, ascending| Rule::Monotonic {
+            strictly,
+            ascending,
+        };
+        let check = |rule: &Rule, code: Vec<u8>| rule.evaluate(Code::new(code)).unwrap();
+
+        assert!(check(&rule(true, true), vec![1, 2, 3]));
+        assert!(check(&rule(false, true), vec![1, 2, 3]));
+        assert!(!check(&rule(true, true), vec![1, 2, 2]));
+        assert!(check(&rule(false, true), vec![1, 2, 2]));
+        assert!(!check(&rule(false, false), vec![1, 2, 2]));
+        assert!(check(&rule(true, false), vec![3, 2, 1]));
+        assert!(!check(&rule(false, true), vec![3, 2, 1]));
+
+        // A single digit is ordered every way
+        assert!(check(&rule(true, true), vec![4]));
+        assert!(check(&rule(true, false), vec![4]));
+
+        let gc = test_configuration(3, 5);
+        // Strictly ascending codes are the 3-digit subsets of the 5 digits
+        assert_eq!(rule(true, true).get_mask(&gc).unwrap().count_ones(), 10);
+        assert_eq!(rule(true, false).to_string(), "Strictly descending");
+    }
+
+    #[test]
+    fn test_column_equals() {
+        let gc = test_configuration(3, 5);
+        let rule = Rule::ColumnEquals(Column::from(0), 2);
+
+        assert!(rule.evaluate(Code::new(vec![2, 0, 4])).unwrap());
+        assert!(!rule.evaluate(Code::new(vec![1, 2, 2])).unwrap());
+        assert_eq!(rule.get_mask(&gc).unwrap().count_ones(), 25);
+        assert_eq!(rule.to_string(), "A == 2");
+
+        let similar = rule.get_similar(&gc);
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].1.len(), 5);
+        assert!(similar[0].1.contains(&rule));
+    }
+
+    #[test]
+    fn test_explain() {
+        let code = Code::new(vec![3, 1, 4]);
+        let explain = |rule: Rule| rule.explain(&code).unwrap();
+        let a = columns(&[0]);
+        let ac = columns(&[0, 2]);
+
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::Pair, a.clone())),
+            "A is 3, so IsPair([A]) does not hold"
+        );
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::Impair, a.clone())),
+            "A is 3, so IsImpair([A]) holds"
+        );
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::Highest, columns(&[2]))),
+            "C is 4, and the highest digit is 4, found in 1 column(s), so IsHighest([C]) holds"
+        );
+        assert!(explain(Rule::MatchesOp(Operator::Lowest, a.clone())).contains("lowest digit is 1"));
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::SumAbove(6), ac.clone())),
+            "the sum of columns [A, C] is 7, so SumAbove([A, C], 6) holds"
+        );
+        assert!(explain(Rule::MatchesOp(Operator::SumBelow(7), ac.clone())).contains("is 7"));
+        assert!(explain(Rule::MatchesOp(Operator::SumEquals(7), ac.clone())).ends_with(" holds"));
+        assert!(
+            explain(Rule::MatchesOp(Operator::ProductEquals(12), ac.clone()))
+                .starts_with("the product of columns [A, C] is 12")
+        );
+        assert!(
+            explain(Rule::MatchesOp(Operator::ProductBelow(12), ac.clone()))
+                .ends_with("does not hold")
+        );
+        assert!(
+            explain(Rule::MatchesOp(Operator::ProductAbove(2), ac.clone())).ends_with(" holds")
+        );
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::SameParity, columns(&[0, 1]))),
+            "A is 3, B is 1, so SameParity([A, B]) holds"
+        );
+        assert!(
+            explain(Rule::MatchesOp(Operator::AlternatingParity, ac.clone()))
+                .starts_with("A is 3, C is 4")
+        );
+        assert!(explain(Rule::MatchesOp(Operator::MaxEquals(4), a.clone()))
+            .starts_with("the highest digit is 4"));
+        assert!(explain(Rule::MatchesOp(Operator::MinEquals(0), a))
+            .starts_with("the lowest digit is 1"));
+
+        assert_eq!(
+            explain(Rule::ColumnEquals(Column::from(1), 1)),
+            "B is 1, so B == 1 holds"
+        );
+        assert!(explain(Rule::XColumnsEquals(1, 4)).starts_with("1 column(s) equal 4"));
+        assert!(explain(Rule::DistinctValues(3)).starts_with("the code uses 3 distinct"));
+        assert!(explain(Rule::Monotonic {
+            strictly: false,
+            ascending: true
+        })
+        .starts_with("the digits read 314"));
+        assert_eq!(
+            explain(Rule::MatchesOp(Operator::Pair, columns(&[2])).negate()),
+            "C is 4, so NOT(IsPair([C])) does not hold"
+        );
+        assert_eq!(
+            explain(Rule::And(
+                Box::new(Rule::MatchesOp(Operator::Pair, columns(&[2]))),
+                Box::new(Rule::MatchesOp(Operator::Impair, columns(&[0])))
+            )),
+            "C is 4; A is 3, so AND(IsPair([C]), IsImpair([A])) holds"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_mask() {
+        let gc = test_configuration(3, 5);
+        let sets = [
+            columns(&[]),
+            columns(&[0]),
+            columns(&[2]),
+            columns(&[0, 1]),
+            columns(&[1, 2]),
+            columns(&[0, 1, 2]),
+        ];
+
+        let mut operators = vec![Operator::Pair, Operator::Impair, Operator::SameParity];
+        for value in 0..=13 {
+            operators.push(Operator::SumBelow(value));
+            operators.push(Operator::SumEquals(value));
+            operators.push(Operator::SumAbove(value));
+            operators.push(Operator::SumMultipleOf(value));
+            operators.push(Operator::SumBetween(value, value + 3));
+        }
+
+        for op in operators {
+            assert!(op.is_arithmetic());
+            for set in sets.iter() {
+                let rule = Rule::MatchesOp(op, set.clone());
+                let fast = rule.get_mask(&gc).unwrap();
+                let evaluated = rule.evaluated_mask(&gc).unwrap();
+                assert_eq!(fast.to_string(), evaluated.to_string(), "{rule}");
+            }
+        }
+
+        assert!(!Operator::AlternatingParity.is_arithmetic());
+        assert!(matches!(
+            Rule::MatchesOp(Operator::Pair, columns(&[3])).get_mask(&gc),
+            Err(EnigmindError::ColumnIndexOutOfBounds)
+        ));
+    }
+
+    /// One of each operator, with its keyword.
+    fn keyword_examples() -> [(&'static str, Operator); 16] {
+        [
+            ("even", Operator::Pair),
+            ("odd", Operator::Impair),
+            ("lowest", Operator::Lowest),
+            ("highest", Operator::Highest),
+            ("sum<7", Operator::SumBelow(7)),
+            ("sum=5", Operator::SumEquals(5)),
+            ("sum>3", Operator::SumAbove(3)),
+            ("sum%3", Operator::SumMultipleOf(3)),
+            ("sum=2..6", Operator::SumBetween(2, 6)),
+            ("product<8", Operator::ProductBelow(8)),
+            ("product=6", Operator::ProductEquals(6)),
+            ("product>4", Operator::ProductAbove(4)),
+            ("same-parity", Operator::SameParity),
+            ("alternating-parity", Operator::AlternatingParity),
+            ("max=3", Operator::MaxEquals(3)),
+            ("min=1", Operator::MinEquals(1)),
+        ]
+    }
+
+    #[test]
+    fn test_keyword_round_trip() {
+        for (keyword, op) in keyword_examples() {
+            assert!(Operator::try_from(keyword).unwrap() == op, "{keyword}");
+            assert_eq!(op.keyword(), keyword);
+        }
+    }
+
+    #[test]
+    fn test_operator_texts_agree() {
+        let numbers = |text: &str| -> Vec<String> {
+            text.split(|c: char| !c.is_ascii_digit())
+                .filter(|number| !number.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        for (keyword, op) in keyword_examples() {
+            // Stops compiling once an operator is added, until it has an example
+            match op {
+                Operator::Pair
+                | Operator::Impair
+                | Operator::Lowest
+                | Operator::Highest
+                | Operator::SumBelow(_)
+                | Operator::SumEquals(_)
+                | Operator::SumAbove(_)
+                | Operator::SumMultipleOf(_)
+                | Operator::SumBetween(..)
+                | Operator::ProductBelow(_)
+                | Operator::ProductEquals(_)
+                | Operator::ProductAbove(_)
+                | Operator::SameParity
+                | Operator::AlternatingParity
+                | Operator::MaxEquals(_)
+                | Operator::MinEquals(_) => (),
+            }
+
+            let description = op.describe();
+            assert!(description.contains(&op.to_string()), "{keyword}");
+            let name = keyword
+                .split(['<', '=', '>', '%'])
+                .next()
+                .unwrap()
+                .replace('-', " ");
+            assert!(description.contains(&name), "{keyword}");
+            assert_eq!(numbers(&description), numbers(keyword), "{keyword}");
+        }
+    }
+
+    #[test]
+    fn test_keyword_rejects_garbage() {
+        for garbage in [
+            "", "evens", "sum", "sum<", "sum<x", "sum<300", "sum!7", "total=3", "max<3",
+            "sum=6..2", " even", "sum = 5",
+        ] {
+            assert!(
+                matches!(
+                    Operator::try_from(garbage),
+                    Err(EnigmindError::ParseError(_) | EnigmindError::InvalidRange { .. })
+                ),
+                "{garbage}"
+            );
+        }
+        assert!(matches!(
+            Operator::try_from("sum=6..2"),
+            Err(EnigmindError::InvalidRange { low: 6, high: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_serde_tags() {
+        let operators = [
+            (Operator::Pair, "Pair"),
+            (Operator::Impair, "Impair"),
+            (Operator::Lowest, "Lowest"),
+            (Operator::Highest, "Highest"),
+            (Operator::SumBelow(7), "SumBelow"),
+            (Operator::SumEquals(5), "SumEquals"),
+            (Operator::SumAbove(3), "SumAbove"),
+            (Operator::SumMultipleOf(3), "SumMultipleOf"),
+            (Operator::SumBetween(2, 6), "SumBetween"),
+            (Operator::ProductBelow(8), "ProductBelow"),
+            (Operator::ProductEquals(6), "ProductEquals"),
+            (Operator::ProductAbove(4), "ProductAbove"),
+            (Operator::SameParity, "SameParity"),
+            (Operator::AlternatingParity, "AlternatingParity"),
+            (Operator::MaxEquals(4), "MaxEquals"),
+            (Operator::MinEquals(1), "MinEquals"),
+        ];
+
+        for (op, tag) in operators {
+            let json = serde_json::to_string(&op).unwrap();
+            assert!(json.starts_with(&format!("{{\"op\":\"{tag}\"")), "{json}");
+            assert!(serde_json::from_str::<Operator>(&json).unwrap() == op);
+        }
+        assert_eq!(
+            serde_json::to_string(&Operator::SumBetween(2, 6)).unwrap(),
+            r#"{"op":"SumBetween","value":[2,6]}"#
+        );
+
+        let rule = Rule::MatchesOp(Operator::SumBelow(7), columns(&[2, 0])).negate();
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(
+            json,
+            r#"{"rule":"Not","args":{"rule":"MatchesOp","args":[{"op":"SumBelow","value":7},[0,2]]}}"#
+        );
+        assert!(serde_json::from_str::<Rule>(&json).unwrap() == rule);
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let cs = columns(&[0, 2]);
+        let rules = vec![
+            Rule::MatchesOp(Operator::Pair, cs.clone()),
+            Rule::MatchesOp(Operator::Impair, cs.clone()),
+            Rule::MatchesOp(Operator::Lowest, columns(&[1])),
+            Rule::MatchesOp(Operator::Highest, columns(&[1])),
+            Rule::MatchesOp(Operator::SumBelow(7), cs.clone()),
+            Rule::MatchesOp(Operator::SumEquals(5), cs.clone()),
+            Rule::MatchesOp(Operator::SumAbove(3), cs.clone()),
+            Rule::MatchesOp(Operator::SumMultipleOf(3), cs.clone()),
+            Rule::MatchesOp(Operator::SumBetween(2, 6), cs.clone()),
+            Rule::MatchesOp(Operator::ProductBelow(8), cs.clone()),
+            Rule::MatchesOp(Operator::ProductEquals(6), cs.clone()),
+            Rule::MatchesOp(Operator::ProductAbove(4), cs.clone()),
+            Rule::MatchesOp(Operator::SameParity, cs.clone()),
+            Rule::MatchesOp(Operator::AlternatingParity, columns(&[0, 1, 2])),
+            Rule::MatchesOp(Operator::MaxEquals(3), columns(&[])),
+            Rule::MatchesOp(Operator::MinEquals(1), columns(&[])),
+            Rule::XColumnsEquals(2, 3),
+            Rule::SetColumnsEqual {
+                columns: cs.clone(),
+                value: 1,
+                count: 2,
+            },
+            Rule::ColumnEquals(Column::from(1), 4),
+            Rule::ColumnCompare {
+                left: Column::from(0),
+                right: Column::from(2),
+                ordering: Ordering::Less,
+            },
+            Rule::ColumnCompare {
+                left: Column::from(1),
+                right: Column::from(0),
+                ordering: Ordering::Equal,
+            },
+            Rule::ColumnCompare {
+                left: Column::from(2),
+                right: Column::from(1),
+                ordering: Ordering::Greater,
+            },
+            Rule::DistinctValues(2),
+            Rule::AdjacentEquals(1),
+            Rule::Monotonic {
+                strictly: false,
+                ascending: true,
+            },
+            Rule::Monotonic {
+                strictly: true,
+                ascending: true,
+            },
+            Rule::Monotonic {
+                strictly: false,
+                ascending: false,
+            },
+            Rule::Monotonic {
+                strictly: true,
+                ascending: false,
+            },
+            Rule::Not(Box::new(Rule::Not(Box::new(Rule::DistinctValues(3))))),
+            Rule::And(
+                Box::new(Rule::MatchesOp(Operator::SumBelow(7), cs.clone())),
+                Box::new(Rule::Or(
+                    Box::new(Rule::ColumnEquals(Column::from(0), 1)),
+                    Box::new(Rule::XColumnsEquals(1, 2)),
+                )),
+            ),
+        ];
+
+        for rule in rules {
+            let text = rule.to_string();
+            let parsed: Rule = text.parse().unwrap();
+            assert!(parsed == rule, "{text}");
+            assert_eq!(parsed.to_string(), text);
+        }
+
+        // Extreme values ignore their columns, which aren't shown
+        let gc = test_configuration(3, 5);
+        let max = Rule::MatchesOp(Operator::MaxEquals(3), columns(&[0, 1, 2]));
+        let parsed: Rule = max.to_string().parse().unwrap();
+        assert_eq!(
+            parsed.get_mask(&gc).unwrap().to_string(),
+            max.get_mask(&gc).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        for garbage in [
+            "",
+            "IsPair",
+            "IsPair(A)",
+            "IsPair([a])",
+            "SumBelow([A], x)",
+            "SumBelow([A])",
+            "Unknown([A])",
+            "A == B",
+            "A <= B",
+            "AND(IsPair([A]))",
+            "SumBetween([A], 6, 2)",
+        ] {
+            assert!(garbage.parse::<Rule>().is_err(), "{garbage}");
+        }
+    }
+}
@@ -1,12 +1,74 @@
-use std::{fmt, ops::Deref};
+use std::{fmt, io::BufRead, ops::Deref};
 
 use serde::{Deserialize, Serialize};
 
-use crate::rule::Rule;
+use crate::{
+    error::EnigmindError,
+    rule::Rule,
+    setup::GameConfiguration,
+    verifier::{MaskSet, Verifier},
+};
 
 #[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Rules(Vec<Rule>);
 
+impl Rules {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Rule> {
+        self.0.get(index)
+    }
+
+    /// Keeps the satisfiable rules accepting more than `min_pct` percent of the possible codes.
+    pub fn filter_by_difficulty(&self, gc: &GameConfiguration, min_pct: u8) -> Rules {
+        self.iter()
+            .filter(|r| {
+                Verifier::new(gc, (*r).clone())
+                    .map(|v| v.is_within_difficulty(gc, min_pct))
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Reads one rule per line, as displayed, skipping blank lines and `#` comments.
+    pub fn from_reader(r: impl BufRead) -> Result<Rules, EnigmindError> {
+        let mut rules = Vec::new();
+        for (index, line) in r.lines().enumerate() {
+            let line = line.map_err(|e| EnigmindError::ParseError(e.to_string()))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let rule = line.parse().map_err(|e| match e {
+                EnigmindError::ParseError(message) => {
+                    EnigmindError::ParseError(format!("line {}: {message}", index + 1))
+                }
+                e => EnigmindError::ParseError(format!("line {}: {e}", index + 1)),
+            })?;
+            rules.push(rule);
+        }
+        Ok(Self(rules))
+    }
+
+    /// Keeps the first of the rules accepting exactly the same codes.
+    pub fn dedup(&mut self, gc: &GameConfiguration) {
+        let mut masks = MaskSet::default();
+        self.0.retain(|rule| match rule.get_mask(gc) {
+            Ok(mask) => masks.insert(&mask),
+            Err(_) => true,
+        });
+    }
+}
+
 impl From<Rules> for Vec<Rule> {
     fn from(rs: Rules) -> Self {
         rs.0
@@ -46,3 +108,76 @@ impl fmt::Display for Rules {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::{
+        column::Column,
+        columns::ColumnSet,
+        rule::{Operator, Rule},
+        setup::test_configuration,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_dedup() {
+        let gc = test_configuration(3, 5);
+        let column_a: ColumnSet = HashSet::from([Column::from(0)]).into();
+
+        let mut rules: Rules = vec![
+            Rule::MatchesOp(Operator::Pair, column_a.clone()),
+            Rule::MatchesOp(Operator::Impair, column_a.clone()),
+            Rule::Not(Box::new(Rule::MatchesOp(Operator::Impair, column_a))),
+            Rule::DistinctValues(1),
+            Rule::MatchesOp(
+                Operator::SameParity,
+                HashSet::from_iter(gc.get_all_columns()).into(),
+            ),
+        ]
+        .into();
+        rules.dedup(&gc);
+
+        assert_eq!(rules.len(), 4);
+        assert!(!rules.iter().any(|rule| matches!(rule, Rule::Not(_))));
+    }
+
+    #[test]
+    fn test_access() {
+        let rules: Rules = vec![Rule::DistinctValues(1), Rule::DistinctValues(2)].into();
+        assert_eq!(rules.len(), 2);
+        assert!(!rules.is_empty());
+        assert!(rules.get(1) == Some(&Rule::DistinctValues(2)));
+        assert!(rules.get(2).is_none());
+
+        let empty: Rules = Vec::new().into();
+        assert!(empty.is_empty());
+        assert!(empty.get(0).is_none());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let file = "# A handcrafted puzzle
+IsPair([A])
+
+SumBelow([B, C], 7)
+  A < C
+# Comments and blank lines are skipped
+NOT(XColumnsEquals(2, 3))
+";
+        let rules = Rules::from_reader(file.as_bytes()).unwrap();
+
+        assert_eq!(rules.len(), 4);
+        assert!(rules[2] == "A < C".parse().unwrap());
+        assert_eq!(
+            rules.to_string(),
+            "IsPair([A])\nSumBelow([B, C], 7)\nA < C\nNOT(XColumnsEquals(2, 3))\n"
+        );
+        assert!(Rules::from_reader(rules.to_string().as_bytes()).is_ok());
+
+        let error = Rules::from_reader("IsPair([A])\nIsPair(A)".as_bytes())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("line 2"));
+    }
+}
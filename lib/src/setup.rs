@@ -1,62 +1,266 @@
 use crate::{
-    code::Code,
+    code::{Code, Codes},
     column::Column,
     columns::ColumnSet,
-    criteria::Criteria,
+    criteria::{Criteria, PublicCriteria},
     criterias::Criterias,
     error::EnigmindError,
-    rule::{Operator, Rule},
+    rule::{Operator, OperatorKind, Rule},
     rules::Rules,
-    term_format::TermFormat,
-    verifier::{Verificators, Verifier},
+    verifier::Verifier,
 };
 use itertools::Itertools;
 use nbitmask::BitMask;
-use pad::PadStr;
-use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, fmt, ops::Deref};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+#[cfg(feature = "generation")]
+use crate::{term_format::TermFormat, verifier::Verificators};
+#[cfg(feature = "generation")]
+use log::{debug, trace};
+#[cfg(feature = "generation")]
+use pad::PadStr;
+#[cfg(feature = "generation")]
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Upper bound on the number of possible codes a generated game may have.
+/// Every rule mask holds one bit per possible code, and generation evaluates all of them.
+pub const MAX_SOLUTION_COUNT: u32 = 10_000;
+
+/// Digits are written as single characters
+pub const MAX_BASE: u8 = 9;
+
+/// Largest threshold of product rules, whose argument is a u8 like for the other operators.
+/// Larger products are only told apart from smaller ones, by the rules with this threshold.
+const MAX_PRODUCT_THRESHOLD: u32 = u8::MAX as u32;
+
+/// Bids allowed per game, so that codes can't be tried one after the other.
+pub const MAX_BIDS: u32 = 3;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg(feature = "generation")]
+// Rule picks allowed per possible code before generation gives up
+const RULE_PICKS_PER_CODE: u32 = 10;
+
+#[cfg(feature = "generation")]
+const MAX_GENERATION_ATTEMPTS: usize = 100;
+
+/// Version of the serialized game format, bumped whenever it changes.
+pub const SCHEMA_VERSION: u16 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GameConfiguration {
+    #[serde(default)]
+    pub schema_version: u16,
     pub column_count: u8,
     pub base: u8,
     pub min_difficulty: u8,
+    /// Number of tests allowed before the game is lost, unlimited when unset
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// Options the game was generated with, unset when they were the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<GenerationSettings>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
     pub configuration: GameConfiguration,
     pub criterias: Criterias,
     pub code: Code,
+    pub seed: u64,
 }
 
-impl Game {
+/// JSON Schema of a serialized `PublicGame`, what clients not written in Rust get to see of a
+/// game.
+#[cfg(feature = "schema")]
+pub fn public_game_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(PublicGame)
+}
+
+/// What players get to see of a game: neither the secret code nor the verifiers, which
+/// together give it away.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PublicGame {
+    pub configuration: GameConfiguration,
+    pub criterias: Vec<PublicCriteria>,
+}
+
+impl PublicGame {
     pub fn is_solution_compatible(&self, code: &Code) -> bool {
-        if code.0.len() != self.configuration.column_count as usize {
-            return false;
+        code.is_well_formed(&self.configuration)
+    }
+
+    pub fn criterion(&self, id: u8) -> Option<&PublicCriteria> {
+        self.criterias.iter().find(|crit| crit.id == id)
+    }
+
+    pub fn criteria_count(&self) -> u8 {
+        self.criterias.len() as u8
+    }
+}
+
+impl From<&Game> for PublicGame {
+    fn from(game: &Game) -> Self {
+        Self {
+            configuration: game.configuration.clone(),
+            criterias: game.criterias.iter().map(PublicCriteria::from).collect(),
         }
+    }
+}
 
-        if code.0.iter().any(|&f| f >= self.configuration.base) {
-            return false;
+/// Steps of `Game::estimated_difficulty` for verifiers with the given masks.
+fn solve_steps(masks: &[&BitMask<u64>], gc: &GameConfiguration) -> u32 {
+    let mut remaining: BitMask<u64> = BitMask::ones(gc.solution_count() as usize);
+    let mut steps = 0;
+
+    while remaining.count_ones() > 1 {
+        let best = masks
+            .iter()
+            .map(|mask| &remaining & mask)
+            .min_by_key(|mask| mask.count_ones());
+
+        match best {
+            Some(mask) if mask.count_ones() < remaining.count_ones() => remaining = mask,
+            _ => break,
         }
-        true
+        steps += 1;
     }
 
-    pub fn to_column_index(&self, column: char) -> u8 {
-        (column as u8) - 65
+    steps
+}
+
+impl Game {
+    /// Handcrafted game verified by the given rules, which must leave a single code.
+    /// Each criteria shows the first grouping of rules similar to its verifier.
+    pub fn from_rules(gc: GameConfiguration, rules: Rules) -> Result<Game, EnigmindError> {
+        gc.validate()?;
+
+        let mut remaining: BitMask<u64> = BitMask::ones(gc.solution_count() as usize);
+        let mut criterias = Vec::new();
+        let mut picked: Vec<String> = Vec::new();
+        for (id, rule) in rules.iter().enumerate() {
+            let verif = Verifier::new(&gc, rule.clone())?;
+            remaining = &remaining & &verif.mask;
+
+            let (description, shown) = rule
+                .get_similar(&gc)
+                .into_iter()
+                .find(|(_, similar)| similar.contains(rule))
+                .unwrap_or_else(|| (rule.to_string(), vec![rule.clone()].into()));
+
+            // Numbered like generated games when several criteria share a description
+            let count = picked
+                .iter()
+                .filter(|picked| **picked == description)
+                .count();
+            picked.push(description.clone());
+
+            criterias.push(Criteria {
+                id: id as u8,
+                info_bits: verif.info_bits(&gc),
+                verif,
+                description: match count {
+                    0 => description,
+                    _ => format!("{description} ({})", count + 1),
+                },
+                rules: shown,
+            });
+        }
+
+        if remaining.count_ones() != 1 {
+            return Err(EnigmindError::NoUniqueSolution);
+        }
+        let code = Code::from_shift(remaining.trailing_zeros() as u32, &gc);
+
+        Ok(Game {
+            configuration: gc,
+            criterias: criterias.into(),
+            code,
+            seed: 0,
+        })
+    }
+
+    pub fn is_solution_compatible(&self, code: &Code) -> bool {
+        code.is_well_formed(&self.configuration)
+    }
+
+    pub fn criterion(&self, id: u8) -> Option<&Criteria> {
+        self.criterias.criterion(id)
+    }
+
+    pub fn criteria_count(&self) -> u8 {
+        self.criterias.len() as u8
+    }
+
+    /// Index of a column letter, in either case, if the code has such a column.
+    pub fn to_column_index(&self, column: char) -> Option<u8> {
+        Column::from_char(column)
+            .map(u8::from)
+            .filter(|index| *index < self.configuration.column_count)
     }
 
     pub fn is_column_compatible(&self, column: char) -> bool {
-        if (column as u8) < 65 {
-            return false;
-        }
-        (column as u8) - 65 < self.configuration.column_count
+        self.to_column_index(column).is_some()
     }
 
     pub fn is_value_compatible(&self, value: u8) -> bool {
         value < self.configuration.base
     }
+
+    pub fn consistent_codes(&self) -> Vec<Code> {
+        self.configuration
+            .codes()
+            .filter(|code| {
+                self.criterias
+                    .iter()
+                    .all(|crit| crit.verif.rule.evaluate(code.clone()).unwrap_or(false))
+            })
+            .collect()
+    }
+
+    /// Number of criteria a perfect logician needs, picking at each step the one
+    /// that narrows the remaining codes the most.
+    pub fn estimated_difficulty(&self) -> u32 {
+        let masks: Vec<&BitMask<u64>> =
+            self.criterias.iter().map(|crit| &crit.verif.mask).collect();
+        solve_steps(&masks, &self.configuration)
+    }
+
+    /// Printable puzzle for solving on paper, without the code nor the verifiers.
+    pub fn to_sheet(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!("EnigMind {}\n\n", self.configuration));
+
+        for crit in self.criterias.iter() {
+            s.push_str(&format!("Criteria {} : {}\n", crit.id, crit.description));
+            for rule in crit.rules.iter() {
+                s.push_str(&format!("  [ ] {rule}\n"));
+            }
+            s.push('\n');
+        }
+
+        let columns = self.configuration.get_all_columns();
+        s.push_str("   ");
+        for column in columns.iter() {
+            s.push_str(&format!(" {column}"));
+        }
+        s.push('\n');
+        for value in (0..self.configuration.base).rev() {
+            s.push_str(&format!("{value:>3}"));
+            for _ in columns.iter() {
+                s.push_str(" .");
+            }
+            s.push('\n');
+        }
+
+        s
+    }
 }
 
 impl fmt::Display for Game {
@@ -69,7 +273,219 @@ impl fmt::Display for Game {
 
 impl GameConfiguration {
     pub fn solution_count(&self) -> u32 {
-        (self.base as u32).pow(self.column_count as u32)
+        (self.base as u32).saturating_pow(self.column_count as u32)
+    }
+
+    pub fn parse_code(&self, s: &str) -> Result<Code, EnigmindError> {
+        let code = Code::from_digits_str(s, self)?;
+        if code.0.len() != self.column_count as usize {
+            return Err(EnigmindError::WrongCodeLength {
+                expected: self.column_count,
+                found: code.0.len(),
+            });
+        }
+        Ok(code)
+    }
+
+    pub fn validate(&self) -> Result<(), EnigmindError> {
+        if !(2..=MAX_BASE).contains(&self.base)
+            || self.column_count < 1
+            || self.checked_solution_count(MAX_SOLUTION_COUNT).is_err()
+        {
+            return Err(EnigmindError::InvalidConfiguration {
+                base: self.base,
+                column_count: self.column_count,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether that many tests go over the allowed attempts.
+    pub fn is_lost(&self, attempts: u32) -> bool {
+        self.max_attempts.is_some_and(|max| attempts > max)
+    }
+
+    pub fn checked_solution_count(&self, max: u32) -> Result<u32, EnigmindError> {
+        (self.base as u32)
+            .checked_pow(self.column_count as u32)
+            .filter(|count| *count <= max)
+            .ok_or(EnigmindError::ConfigurationTooLarge)
+    }
+
+    pub fn check_schema_version(&self) -> Result<(), EnigmindError> {
+        match self.schema_version {
+            SCHEMA_VERSION => Ok(()),
+            version => Err(EnigmindError::UnsupportedSchemaVersion(version)),
+        }
+    }
+
+    /// Every rule that could describe a code of this configuration, before any difficulty filtering.
+    pub fn all_candidate_rules(&self) -> Result<Rules, EnigmindError> {
+        let mut rules = Vec::new();
+
+        self.get_column_combinations(1).iter().for_each(|cs| {
+            rules.push(Rule::MatchesOp(Operator::Pair, cs.clone()));
+            rules.push(Rule::MatchesOp(Operator::Impair, cs.clone()));
+            rules.push(Rule::MatchesOp(Operator::Lowest, cs.clone()));
+            rules.push(Rule::MatchesOp(Operator::Highest, cs.clone()));
+        });
+
+        // Listed before the single column sums, which accept the same codes
+        for column in self.get_all_columns() {
+            for value in 0..self.base {
+                rules.push(Rule::ColumnEquals(column, value));
+            }
+        }
+
+        for c_cart_prod in self.get_all_column_combinations() {
+            for base in 0..((c_cart_prod.clone().len() as u8) * self.base) {
+                rules.push(Rule::MatchesOp(
+                    Operator::SumBelow(base),
+                    c_cart_prod.clone(),
+                ));
+                rules.push(Rule::MatchesOp(
+                    Operator::SumEquals(base),
+                    c_cart_prod.clone(),
+                ));
+                rules.push(Rule::MatchesOp(
+                    Operator::SumAbove(base),
+                    c_cart_prod.clone(),
+                ));
+            }
+        }
+
+        // Every sum is a multiple of 1, and 0 is not a modulus
+        for c_cart_prod in self.get_all_column_combinations() {
+            for modulus in 2..=self.base {
+                rules.push(Rule::MatchesOp(
+                    Operator::SumMultipleOf(modulus),
+                    c_cart_prod.clone(),
+                ));
+            }
+        }
+
+        // Ranges touching the lowest or highest sum are already covered by SumBelow and SumAbove
+        for c_cart_prod in self.get_all_column_combinations() {
+            let max_sum = c_cart_prod.len() as u8 * (self.base - 1);
+            for low in 1..max_sum {
+                for high in (low + 1)..max_sum.min(low + self.base / 2 + 1) {
+                    rules.push(Rule::MatchesOp(
+                        Operator::sum_between(low, high)?,
+                        c_cart_prod.clone(),
+                    ));
+                }
+            }
+        }
+
+        // Parity across a single column is always satisfied
+        for c_cart_prod in self.get_all_column_combinations() {
+            if c_cart_prod.len() < 2 {
+                continue;
+            }
+            rules.push(Rule::MatchesOp(Operator::SameParity, c_cart_prod.clone()));
+            rules.push(Rule::MatchesOp(
+                Operator::AlternatingParity,
+                c_cart_prod.clone(),
+            ));
+        }
+
+        // A single column product is its own value, which sum rules already cover
+        for c_cart_prod in self.get_all_column_combinations() {
+            if c_cart_prod.len() < 2 {
+                continue;
+            }
+            let max_product = (self.base as u32 - 1)
+                .pow(c_cart_prod.len() as u32)
+                .min(MAX_PRODUCT_THRESHOLD);
+            for value in 0..=max_product as u8 {
+                rules.push(Rule::MatchesOp(
+                    Operator::ProductBelow(value),
+                    c_cart_prod.clone(),
+                ));
+                rules.push(Rule::MatchesOp(
+                    Operator::ProductEquals(value),
+                    c_cart_prod.clone(),
+                ));
+                rules.push(Rule::MatchesOp(
+                    Operator::ProductAbove(value),
+                    c_cart_prod.clone(),
+                ));
+            }
+        }
+
+        for column in 0..=self.column_count {
+            for base in 0..self.base {
+                rules.push(Rule::XColumnsEquals(column, base));
+            }
+        }
+
+        // Single columns are ColumnEquals and the whole code XColumnsEquals, larger sets
+        // would give too many rules
+        for columns in self.get_all_column_combinations() {
+            if columns.len() < 2 || columns.len() > 3 || columns.len() == self.column_count as usize
+            {
+                continue;
+            }
+            for value in 0..self.base {
+                for count in 0..=columns.len() as u8 {
+                    rules.push(Rule::SetColumnsEqual {
+                        columns: columns.clone(),
+                        value,
+                        count,
+                    });
+                }
+            }
+        }
+
+        // Extreme values ignore the column set, so a single set covering the whole code is enough
+        for all_columns in self.get_column_combinations(self.column_count) {
+            for value in 0..self.base {
+                rules.push(Rule::MatchesOp(
+                    Operator::MaxEquals(value),
+                    all_columns.clone(),
+                ));
+                rules.push(Rule::MatchesOp(
+                    Operator::MinEquals(value),
+                    all_columns.clone(),
+                ));
+            }
+        }
+
+        for count in 1..=self.column_count {
+            rules.push(Rule::DistinctValues(count));
+        }
+
+        for count in 0..self.column_count {
+            rules.push(Rule::AdjacentEquals(count));
+        }
+
+        // A single digit is always ordered
+        if self.column_count >= 2 {
+            for strictly in [false, true] {
+                for ascending in [true, false] {
+                    rules.push(Rule::Monotonic {
+                        strictly,
+                        ascending,
+                    });
+                }
+            }
+        }
+
+        for (left, right) in self.get_all_column_pairs() {
+            for ordering in [Ordering::Less, Ordering::Equal, Ordering::Greater] {
+                rules.push(Rule::ColumnCompare {
+                    left,
+                    right,
+                    ordering,
+                });
+            }
+        }
+
+        Ok(rules.into())
+    }
+
+    pub fn codes(&self) -> Codes<'_> {
+        Codes::new(self)
     }
 
     pub fn get_all_columns(&self) -> Vec<Column> {
@@ -90,7 +506,7 @@ impl GameConfiguration {
         v
     }
 
-    pub fn get_all_column_combinations(&self) -> HashSet<ColumnSet> {
+    pub fn get_all_column_combinations(&self) -> Vec<ColumnSet> {
         let mut all_cartesian_prods = HashSet::new();
 
         let mut multi_prod = (0..self.column_count)
@@ -104,10 +520,15 @@ impl GameConfiguration {
 
             opt = multi_prod.next();
         }
+
+        // Sort the combinations so that seeded generation doesn't depend on hashing order
         all_cartesian_prods
+            .into_iter()
+            .sorted_by_key(|cs| cs.sorted())
+            .collect()
     }
 
-    pub fn get_column_combinations(&self, length: u8) -> HashSet<ColumnSet> {
+    pub fn get_column_combinations(&self, length: u8) -> Vec<ColumnSet> {
         let mut res = self.get_all_column_combinations();
 
         res.retain(|cs| cs.len() == length as usize);
@@ -143,7 +564,7 @@ impl GameConfiguration {
                 }
             }
             dbg!(l);
-            println!("{:?}", res.clone());
+            trace!("{:?}", res.clone());
             res
         }
 
@@ -163,114 +584,351 @@ impl fmt::Display for GameConfiguration {
     }
 }
 
+/// Configuration without difficulty nor attempt limit, shared by the unit tests.
+#[cfg(test)]
+pub(crate) fn test_configuration(column_count: u8, base: u8) -> GameConfiguration {
+    GameConfiguration {
+        schema_version: SCHEMA_VERSION,
+        column_count,
+        base,
+        min_difficulty: 0,
+        max_attempts: None,
+        generation: None,
+    }
+}
+
+#[cfg(feature = "generation")]
 fn generate_game_configuration(
     base: u8,
     column_count: u8,
     difficulty_pct: u8,
 ) -> GameConfiguration {
     GameConfiguration {
+        schema_version: SCHEMA_VERSION,
         column_count,
         base,
         min_difficulty: difficulty_pct.clamp(0, 100),
+        max_attempts: None,
+        generation: None,
     }
 }
 
-fn generate_rules(gc: &GameConfiguration) -> Result<Rules, EnigmindError> {
-    let mut rules = Vec::new();
+#[cfg(feature = "generation")]
+// Masks are computed once here, rule picking then only combines them
+fn generate_rules(
+    gc: &GameConfiguration,
+    options: &GenerationOptions,
+) -> Result<Verificators, EnigmindError> {
+    let rules: Vec<Rule> = gc
+        .all_candidate_rules()?
+        .iter()
+        .filter(|rule| rule.kinds().is_subset(&options.allowed_operators))
+        .cloned()
+        .collect();
+    #[cfg(feature = "parallel")]
+    let candidates = parallel_verifiers(gc, &rules)?;
+    #[cfg(not(feature = "parallel"))]
+    let candidates = sequential_verifiers(gc, &rules)?;
 
-    gc.get_column_combinations(1).iter().for_each(|cs| {
-        rules.push(Rule::MatchesOp(Operator::Pair, cs.clone()));
-        rules.push(Rule::MatchesOp(Operator::Impair, cs.clone()));
-        rules.push(Rule::MatchesOp(Operator::Lowest, cs.clone()));
-        rules.push(Rule::MatchesOp(Operator::Highest, cs.clone()));
-    });
+    let mut candidates: Verificators = candidates
+        .into_iter()
+        .inspect(|candidate| {
+            trace!(
+                "Rule {} bitmask {}",
+                candidate.rule.formatted_with(options.colored_logs),
+                candidate.mask
+            )
+        })
+        .filter(|candidate| candidate.is_within_difficulty(gc, gc.min_difficulty))
+        .collect::<Vec<_>>()
+        .into();
+    candidates.dedup();
 
-    for c_cart_prod in gc.get_all_column_combinations() {
-        for base in 0..((c_cart_prod.clone().len() as u8) * gc.base) {
-            rules.push(Rule::MatchesOp(
-                Operator::SumBelow(base),
-                c_cart_prod.clone(),
-            ));
-            rules.push(Rule::MatchesOp(
-                Operator::SumEquals(base),
-                c_cart_prod.clone(),
-            ));
-            rules.push(Rule::MatchesOp(
-                Operator::SumAbove(base),
-                c_cart_prod.clone(),
-            ));
+    debug!(
+        "Total rules generated (filtered by difficulty and deduplicated): {}",
+        candidates.len()
+    );
+
+    Ok(candidates)
+}
+
+/// Verifiers of the given rules, in the same order, their masks computed one after the other.
+#[cfg(feature = "generation")]
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn sequential_verifiers(
+    gc: &GameConfiguration,
+    rules: &[Rule],
+) -> Result<Vec<Verifier>, EnigmindError> {
+    rules
+        .iter()
+        .map(|rule| Verifier::new(gc, rule.clone()))
+        .collect()
+}
+
+/// Same as `sequential_verifiers`, masks being computed across threads. Masks are most of the
+/// generation time for base 5 and 5 columns, about 1.6s a game on a single core, which this
+/// divides by up to the number of cores since every mask is independent.
+#[cfg(feature = "parallel")]
+fn parallel_verifiers(
+    gc: &GameConfiguration,
+    rules: &[Rule],
+) -> Result<Vec<Verifier>, EnigmindError> {
+    use rayon::prelude::*;
+
+    rules
+        .par_iter()
+        .map(|rule| Verifier::new(gc, rule.clone()))
+        .collect()
+}
+
+/// How rules are picked until a single code is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SelectionStrategy {
+    /// Any rule at random, skipping those which don't narrow the remaining codes
+    #[default]
+    RandomGreedy,
+    /// Among the rules narrowing the remaining codes, one at random of those giving the most
+    /// information, that is leaving the fewest codes
+    MaxEntropy,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationOptions {
+    pub base: u8,
+    pub column_count: u8,
+    pub min_difficulty: u8,
+    /// Random when unset
+    pub seed: Option<u64>,
+    pub max_criteria: Option<u8>,
+    /// Keep the smallest set of verifiers leading to the code, instead of any irreducible one.
+    /// Past 16 picked verifiers trying every subset gets too slow, and an irreducible set is
+    /// kept instead.
+    pub minimize: bool,
+    pub max_attempts: Option<u32>,
+    pub strategy: SelectionStrategy,
+    /// Exact number of criteria, padding with verifiers the code doesn't need when too few
+    pub target_criteria: Option<u8>,
+    /// Rules picked before giving up on a game, ten per possible code when unset
+    pub max_rule_picks: Option<u32>,
+    /// Inclusive range of `Game::estimated_difficulty` to generate games in, unlike
+    /// `min_difficulty` which only applies to rules one by one
+    pub difficulty_band: Option<(u32, u32)>,
+    /// Families of rules games are made of, all of them by default
+    pub allowed_operators: BTreeSet<OperatorKind>,
+    /// Highlight rules in the generation logs, for callers whose logs go to a terminal
+    pub colored_logs: bool,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            base: 5,
+            column_count: 3,
+            min_difficulty: 0,
+            seed: None,
+            max_criteria: None,
+            minimize: false,
+            max_attempts: None,
+            strategy: SelectionStrategy::RandomGreedy,
+            target_criteria: None,
+            max_rule_picks: None,
+            difficulty_band: None,
+            allowed_operators: BTreeSet::from(OperatorKind::ALL),
+            colored_logs: false,
         }
     }
+}
 
-    for column in 0..=gc.column_count {
-        for base in 0..gc.base {
-            rules.push(Rule::XColumnsEquals(column, base));
+impl GenerationOptions {
+    fn settings(&self) -> GenerationSettings {
+        GenerationSettings {
+            max_criteria: self.max_criteria,
+            minimize: self.minimize,
+            strategy: self.strategy,
+            target_criteria: self.target_criteria,
+            max_rule_picks: self.max_rule_picks,
+            difficulty_band: self.difficulty_band,
+            allowed_operators: self.allowed_operators.clone(),
         }
     }
+}
+
+/// Generation options a game's configuration doesn't already hold, kept along with it so
+/// that `Game::replay` generates the same game again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GenerationSettings {
+    pub max_criteria: Option<u8>,
+    pub minimize: bool,
+    pub strategy: SelectionStrategy,
+    pub target_criteria: Option<u8>,
+    pub max_rule_picks: Option<u32>,
+    pub difficulty_band: Option<(u32, u32)>,
+    pub allowed_operators: BTreeSet<OperatorKind>,
+}
 
-    for r in rules.iter() {
-        println!("Rule {} bitmask {}", r.formatted(), r.get_mask(gc)?);
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        GenerationOptions::default().settings()
     }
+}
 
-    rules.retain(|r| {
-        r.get_mask(gc)
-            .map(|mask| {
-                let ones_count = mask.count_ones();
-                let difficulty = ones_count * 100 / gc.solution_count() as usize;
-                ones_count > 0 && difficulty > gc.min_difficulty as usize
-            })
-            .unwrap_or(false)
-    });
-    println!(
-        "Total rules generated (filtered by difficulty): {}",
-        rules.len()
-    );
+/// Candidates leaving the fewest of the remaining codes, at least one and not all. Halving
+/// the codes at each pick would need at least log2 of their count verifiers, more than
+/// random picks end up with.
+#[cfg(feature = "generation")]
+fn most_informative<'a>(
+    candidates: &'a Verificators,
+    remaining: &BitMask<u64>,
+) -> Vec<&'a Verifier> {
+    let total = remaining.count_ones();
+    let mut best = Vec::new();
+    let mut best_left = usize::MAX;
+    for candidate in candidates.iter() {
+        let left = (remaining & &candidate.mask).count_ones();
+        if left == 0 || left == total {
+            continue;
+        }
 
-    Ok(rules.into())
+        if left < best_left {
+            best_left = left;
+            best.clear();
+        }
+        if left == best_left {
+            best.push(candidate);
+        }
+    }
+    best
 }
 
+#[cfg(feature = "generation")]
 fn generate_verificators(
-    ruleset: &Rules,
+    candidates: &Verificators,
     gc: &GameConfiguration,
+    options: &GenerationOptions,
+    rng: &mut impl Rng,
 ) -> Result<(Code, Verificators), EnigmindError> {
     let mut verificators_before_cleanup = Vec::new();
     let mut final_bitmask: BitMask<u64> = BitMask::ones(gc.solution_count() as usize);
 
-    println!("Picking rules until a single solution is found");
+    debug!("Picking rules until a single solution is found");
+    let max_picks = options
+        .max_rule_picks
+        .unwrap_or(gc.solution_count() * RULE_PICKS_PER_CODE);
+    let mut picks = 0;
+    // Picks in a row which didn't narrow the codes
+    let mut misses = 0;
     //While more than one solution
     while final_bitmask.count_ones() > 1 {
-        let rule = ruleset.choose(&mut rand::thread_rng()).unwrap();
-        let rule_bitmask = rule.get_mask(gc)?;
-        let bitmask_and = &final_bitmask & &rule_bitmask;
+        picks += 1;
+        if picks > max_picks {
+            return Err(EnigmindError::GenerationTimeout);
+        }
+
+        let candidate = match options.strategy {
+            SelectionStrategy::RandomGreedy => candidates.choose(rng),
+            SelectionStrategy::MaxEntropy => most_informative(candidates, &final_bitmask)
+                .choose(rng)
+                .copied(),
+        }
+        .ok_or(EnigmindError::NoRulesAvailable)?;
+        let bitmask_and = &final_bitmask & &candidate.mask;
 
         let msg;
         if bitmask_and.count_ones() == 0 {
             msg = "skipped (0 sols).".to_string();
+            misses += 1;
         } else if bitmask_and == final_bitmask {
             msg = "skipped (0 impr).".to_string();
+            misses += 1;
         } else {
-            verificators_before_cleanup.push(Verifier {
-                rule: rule.clone(),
-                mask: rule_bitmask.clone(),
-            });
+            verificators_before_cleanup.push(candidate.clone());
 
             final_bitmask = bitmask_and;
             msg = "chosen.".to_string();
+            misses = 0;
         }
-        println!(
+        trace!(
             "{} {} Remaining bitmask : {} ({})",
-            rule.formatted(),
+            candidate.rule.formatted_with(options.colored_logs),
             msg.pad_to_width(18),
             final_bitmask,
-            rule_bitmask.count_ones()
+            candidate.mask.count_ones()
         );
+
+        // Once as many picks as there are rules missed, make sure some rule is left to pick
+        if misses >= candidates.len() {
+            if most_informative(candidates, &final_bitmask).is_empty() {
+                return Err(EnigmindError::NoUniqueSolution);
+            }
+            misses = 0;
+        }
     }
 
-    println!(
+    debug!(
         "Total number of rules generated : {}",
         verificators_before_cleanup.len()
     );
 
+    // Irreducible sets too large for the target fall back to the smallest one
+    let final_verificators = match (options.minimize, options.target_criteria) {
+        (true, _) => minimal_verificators(&verificators_before_cleanup, gc),
+        (false, None) => remove_redundant_verificators(verificators_before_cleanup, gc),
+        (false, Some(target)) => {
+            let irreducible =
+                remove_redundant_verificators(verificators_before_cleanup.clone(), gc);
+            match irreducible.len() > target as usize {
+                true => minimal_verificators(&verificators_before_cleanup, gc),
+                false => irreducible,
+            }
+        }
+    };
+
+    let code = Code::from_shift(final_bitmask.trailing_zeros() as u32, gc);
+    Ok((code, final_verificators.into()))
+}
+
+/// Adds verifiers accepting the code until there are `target` of them. Each added one neither
+/// implies nor is implied by another verifier, so it doesn't read as a restatement of it.
+/// None when there aren't enough such verifiers.
+#[cfg(feature = "generation")]
+fn pad_verificators(
+    verificators: Verificators,
+    candidates: &Verificators,
+    code: &Code,
+    target: usize,
+    rng: &mut impl Rng,
+) -> Option<Verificators> {
+    let implies = |a: &Verifier, b: &Verifier| &a.mask & &b.mask == a.mask;
+
+    let mut padded: Vec<Verifier> = verificators.into();
+    let mut candidates: Vec<&Verifier> = candidates
+        .iter()
+        .filter(|candidate| candidate.rule.evaluate(code.clone()).unwrap_or(false))
+        .collect();
+    candidates.shuffle(rng);
+
+    for candidate in candidates {
+        if padded.len() >= target {
+            break;
+        }
+        if padded
+            .iter()
+            .all(|v| !implies(v, candidate) && !implies(candidate, v))
+        {
+            padded.push(candidate.clone());
+        }
+    }
+
+    (padded.len() == target).then(|| padded.into())
+}
+
+#[cfg(feature = "generation")]
+fn remove_redundant_verificators(
+    mut verificators_before_cleanup: Vec<Verifier>,
+    gc: &GameConfiguration,
+) -> Vec<Verifier> {
     verificators_before_cleanup.sort_by_key(|v| v.mask.count_ones());
     verificators_before_cleanup.reverse();
 
@@ -280,7 +938,6 @@ fn generate_verificators(
         let mut other_bitmask = BitMask::ones(gc.solution_count() as usize);
         for other_verificator in &verificators_before_cleanup {
             if *other_verificator != *v {
-                //println!("\tAgainst {}", other_verificator);
                 other_bitmask &= &other_verificator.mask;
             }
         }
@@ -291,73 +948,285 @@ fn generate_verificators(
         is_rule_useful
     });
 
-    let code = Code::from_shift(final_bitmask.trailing_zeros() as u32, gc);
-    Ok((code, final_verificators.into()))
+    final_verificators
+}
+
+#[cfg(feature = "generation")]
+// Past this many verifiers, trying every subset gets too slow
+const MAX_MINIMIZED_VERIFICATORS: usize = 16;
+
+#[cfg(feature = "generation")]
+fn minimal_verificators(verificators: &[Verifier], gc: &GameConfiguration) -> Vec<Verifier> {
+    if verificators.len() > MAX_MINIMIZED_VERIFICATORS {
+        debug!(
+            "{} verifiers are too many to minimize, only removing redundant ones",
+            verificators.len()
+        );
+        return remove_redundant_verificators(verificators.to_vec(), gc);
+    }
+
+    for size in 1..verificators.len() {
+        for subset in verificators.iter().combinations(size) {
+            let mut mask = BitMask::ones(gc.solution_count() as usize);
+            for v in subset.iter() {
+                mask &= &v.mask;
+            }
+            if mask.count_ones() == 1 {
+                return subset.into_iter().cloned().collect();
+            }
+        }
+    }
+
+    verificators.to_vec()
 }
 
+#[cfg(feature = "generation")]
 fn generate_criterias(
-    _rules: &Rules,
+    _candidates: &Verificators,
     verificators: &Verificators,
     gc: &GameConfiguration,
-) -> Vec<Criteria> {
+    rng: &mut impl Rng,
+) -> Result<Vec<Criteria>, EnigmindError> {
     let mut criterias = Vec::new();
-    for verif in verificators.deref() {
+    let mut picked: Vec<String> = Vec::new();
+    for verif in verificators.iter() {
         let sim_rules = verif.rule.get_similar(gc);
-        let (description, rules) = sim_rules.choose(&mut rand::thread_rng()).unwrap();
+
+        // Players refer to criterias by their description, so groupings already
+        // picked are avoided, and numbered when nothing else is left
+        let unused: Vec<&(String, Rules)> = sim_rules
+            .iter()
+            .filter(|(description, _)| !picked.contains(description))
+            .collect();
+        let (description, rules) = match unused.choose(rng) {
+            Some(similar) => (*similar).clone(),
+            None => sim_rules
+                .choose(rng)
+                .ok_or(EnigmindError::NoRulesAvailable)?
+                .clone(),
+        };
+
+        let count = picked
+            .iter()
+            .filter(|picked| **picked == description)
+            .count();
+        picked.push(description.clone());
 
         criterias.push(Criteria {
+            id: 0,
             verif: verif.clone(),
-            description: description.clone(),
-            rules: rules.clone(),
+            description: match count {
+                0 => description,
+                _ => format!("{description} ({})", count + 1),
+            },
+            rules,
+            info_bits: verif.info_bits(gc),
         });
     }
-    criterias
+    Ok(criterias)
 }
 
-pub fn generate_game(
+#[cfg(feature = "generation")]
+pub fn generate_game_simple(
     base: u8,
     column_count: u8,
     difficulty_pct: u8,
 ) -> Result<Game, EnigmindError> {
-    let gc = generate_game_configuration(base, column_count, difficulty_pct);
-    let rules = generate_rules(&gc)?;
+    generate_game(GenerationOptions {
+        base,
+        column_count,
+        min_difficulty: difficulty_pct,
+        ..Default::default()
+    })
+}
 
-    println!(
+#[cfg(feature = "generation")]
+pub fn generate_game_seeded(
+    base: u8,
+    column_count: u8,
+    difficulty_pct: u8,
+    seed: u64,
+) -> Result<Game, EnigmindError> {
+    generate_game(GenerationOptions {
+        base,
+        column_count,
+        min_difficulty: difficulty_pct,
+        seed: Some(seed),
+        ..Default::default()
+    })
+}
+
+#[cfg(feature = "generation")]
+impl Game {
+    /// Generates again the game seeded with `seed` for this configuration, so storing both is
+    /// enough, the configuration keeping the options the game was generated with.
+    pub fn replay(configuration: &GameConfiguration, seed: u64) -> Result<Game, EnigmindError> {
+        configuration.check_schema_version()?;
+
+        let settings = configuration.generation.clone().unwrap_or_default();
+        generate_game(GenerationOptions {
+            base: configuration.base,
+            column_count: configuration.column_count,
+            min_difficulty: configuration.min_difficulty,
+            seed: Some(seed),
+            max_attempts: configuration.max_attempts,
+            max_criteria: settings.max_criteria,
+            minimize: settings.minimize,
+            strategy: settings.strategy,
+            target_criteria: settings.target_criteria,
+            max_rule_picks: settings.max_rule_picks,
+            difficulty_band: settings.difficulty_band,
+            allowed_operators: settings.allowed_operators,
+            ..Default::default()
+        })
+    }
+}
+
+/// A secret code alone, without any criteria, for games where the player guesses it blindly.
+#[cfg(feature = "generation")]
+pub fn generate_secret(
+    base: u8,
+    column_count: u8,
+    seed: Option<u64>,
+) -> Result<Code, EnigmindError> {
+    let gc = generate_game_configuration(base, column_count, 0);
+    gc.validate()?;
+
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    Ok(Code::from_shift(rng.gen_range(0..gc.solution_count()), &gc))
+}
+
+#[cfg(feature = "generation")]
+pub fn generate_game(options: GenerationOptions) -> Result<Game, EnigmindError> {
+    generate_game_with_rng(options, &mut rand::thread_rng())
+}
+
+/// Same as `generate_game`, drawing the seed from `rng` when the options have none.
+#[cfg(feature = "generation")]
+pub fn generate_game_with_rng(
+    options: GenerationOptions,
+    rng: &mut impl Rng,
+) -> Result<Game, EnigmindError> {
+    let seed = options.seed.unwrap_or_else(|| rng.gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let settings = options.settings();
+    let gc = GameConfiguration {
+        max_attempts: options.max_attempts,
+        generation: (settings != GenerationSettings::default()).then_some(settings),
+        ..generate_game_configuration(options.base, options.column_count, options.min_difficulty)
+    };
+    gc.validate()?;
+    let candidates = generate_rules(&gc, &options)?;
+
+    trace!(
         "Rules generated from configuration {:?}: {}\n{}",
         gc,
-        rules.len(),
-        rules.formatted()
+        candidates.len(),
+        candidates.formatted_with(options.colored_logs)
     );
 
     //pick rules randomly and generate according verificators
-    let (code, verificators) = generate_verificators(&rules, &gc)?;
+    let mut attempts = 0;
+    let (code, verificators) = loop {
+        attempts += 1;
+        let (code, verificators) = generate_verificators(&candidates, &gc, &options, &mut rng)?;
+        let count = verificators.len();
+        let verificators = match options.target_criteria {
+            Some(target) if count < target as usize => {
+                pad_verificators(verificators, &candidates, &code, target as usize, &mut rng)
+            }
+            _ => Some(verificators),
+        };
+
+        match verificators {
+            Some(verificators)
+                if options
+                    .max_criteria
+                    .is_some_and(|max| verificators.len() > max as usize) =>
+            {
+                debug!(
+                    "{} criterias is over the maximum of {:?}",
+                    verificators.len(),
+                    options.max_criteria
+                );
+            }
+            Some(verificators)
+                if options
+                    .target_criteria
+                    .is_some_and(|target| verificators.len() != target as usize) =>
+            {
+                debug!(
+                    "{} criterias is {} the target of {:?}",
+                    verificators.len(),
+                    match options
+                        .target_criteria
+                        .is_some_and(|target| verificators.len() > target as usize)
+                    {
+                        true => "over",
+                        false => "under",
+                    },
+                    options.target_criteria
+                );
+            }
+            Some(verificators)
+                if options.difficulty_band.is_some_and(|(low, high)| {
+                    let masks: Vec<&BitMask<u64>> = verificators.iter().map(|v| &v.mask).collect();
+                    !(low..=high).contains(&solve_steps(&masks, &gc))
+                }) =>
+            {
+                debug!(
+                    "Estimated difficulty is out of the {:?} band",
+                    options.difficulty_band
+                );
+            }
+            Some(verificators) => break (code, verificators),
+            None => debug!(
+                "Not enough verifiers to pad {count} criterias up to {:?}",
+                options.target_criteria
+            ),
+        }
+        if attempts >= MAX_GENERATION_ATTEMPTS {
+            return Err(EnigmindError::CannotSatisfyConstraints);
+        }
+    };
 
     let sum_complexity: u32 = verificators
         .iter()
         .map(|x| x.mask.count_ones() as u32)
         .sum();
-    let mean_complexity = sum_complexity / verificators.len() as u32;
-    println!();
-    println!(
+    let mean_complexity = sum_complexity
+        .checked_div(verificators.len() as u32)
+        .unwrap_or(0);
+    debug!(
         "Set of final {} rules (complexity : {}) used to give the unique answer {}:\n{}",
         verificators.len(),
         mean_complexity,
         code,
-        verificators.formatted()
+        verificators.formatted_with(options.colored_logs)
     );
 
     let mut final_mask = BitMask::ones(gc.solution_count() as usize);
-    for v in verificators.deref() {
+    for v in verificators.iter() {
         final_mask &= &v.mask;
     }
 
     //generate criterias from verificatorset with rules from ruleset
-    let criterias = generate_criterias(&rules, &verificators, &gc);
+    let mut criterias = generate_criterias(&candidates, &verificators, &gc, &mut rng)?;
+
+    // Order criterias on what players can see, so the order gives no clue about the verifiers
+    criterias.sort_by_cached_key(|crit| (crit.description.clone(), crit.rules.to_string()));
+    for (id, crit) in criterias.iter_mut().enumerate() {
+        crit.id = id as u8;
+    }
 
     for crit in &criterias {
-        println!("Criteria chosen for {}", crit.verif.rule.formatted());
-        println!("\"{}\"", crit.description);
-        println!("{}", crit.rules.formatted());
+        debug!(
+            "Criteria chosen for {}\n\"{}\"\n{}",
+            crit.verif.rule.formatted_with(options.colored_logs),
+            crit.description,
+            crit.rules.formatted_with(options.colored_logs)
+        );
     }
 
     //generate game object from criterias, secret code and game configuration
@@ -365,21 +1234,791 @@ pub fn generate_game(
         configuration: gc,
         criterias: criterias.into(),
         code,
+        seed,
     })
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "generation"))]
 mod tests {
-    use super::GameConfiguration;
+    use super::{
+        generate_game, generate_game_configuration, generate_game_seeded, generate_game_simple,
+        generate_secret, generate_verificators, minimal_verificators,
+        remove_redundant_verificators, test_configuration, Game, GameConfiguration,
+        GenerationOptions, PublicGame, SelectionStrategy, MAX_BASE, MAX_SOLUTION_COUNT,
+        SCHEMA_VERSION,
+    };
+    use crate::{
+        code::Code,
+        column::Column,
+        columns::ColumnSet,
+        criteria::Criteria,
+        error::EnigmindError,
+        rule::{Operator, OperatorKind, Rule},
+        rules::Rules,
+        verifier::{Verificators, Verifier},
+    };
+    use nbitmask::BitMask;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{
+        cmp::Ordering,
+        collections::{BTreeSet, HashSet},
+        mem::discriminant,
+    };
+
+    fn handcrafted_game(rules: Vec<Rule>) -> Game {
+        let gc = test_configuration(2, 3);
+        let criterias = rules
+            .into_iter()
+            .enumerate()
+            .map(|(id, rule)| {
+                let verif = Verifier::new(&gc, rule.clone()).unwrap();
+                Criteria {
+                    id: id as u8,
+                    info_bits: verif.info_bits(&gc),
+                    verif,
+                    description: rule.to_string(),
+                    rules: vec![rule].into(),
+                }
+            })
+            .collect();
+
+        Game {
+            configuration: gc,
+            criterias,
+            code: Code::new(vec![0, 0]),
+            seed: 0,
+        }
+    }
+
+    fn columns(indexes: &[u8]) -> ColumnSet {
+        HashSet::from_iter(indexes.iter().map(|i| Column::from(*i))).into()
+    }
 
     #[test]
     fn test_combination() {
-        let gc = GameConfiguration {
-            column_count: 3,
-            base: 5,
-            min_difficulty: 0,
-        };
+        let gc = test_configuration(3, 5);
 
         assert_eq!(gc.get_column_combinations(2).len(), 3);
     }
+
+    #[test]
+    fn test_criterion() {
+        let game = handcrafted_game(vec![
+            Rule::ColumnEquals(Column::from(0), 0),
+            Rule::ColumnEquals(Column::from(1), 0),
+        ]);
+
+        assert_eq!(game.criteria_count(), 2);
+        assert_eq!(game.criterion(1).unwrap().id, 1);
+        assert!(game.criterion(2).is_none());
+
+        let public = PublicGame::from(&game);
+        assert_eq!(public.criteria_count(), 2);
+        assert_eq!(public.criterion(0).unwrap().description, "A == 0");
+        assert!(public.criterion(u8::MAX).is_none());
+
+        let empty = handcrafted_game(vec![]);
+        assert_eq!(empty.criteria_count(), 0);
+        assert!(empty.criterion(0).is_none());
+    }
+
+    #[test]
+    fn test_check_schema_version() {
+        let mut gc = handcrafted_game(vec![]).configuration;
+        assert!(gc.check_schema_version().is_ok());
+
+        gc.schema_version = SCHEMA_VERSION + 1;
+        assert!(matches!(
+            gc.check_schema_version(),
+            Err(EnigmindError::UnsupportedSchemaVersion(v)) if v == SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_all_candidate_rules_cover_every_operator() {
+        let gc = generate_game_configuration(5, 3, 0);
+        let rules = gc.all_candidate_rules().unwrap();
+
+        let operators = [
+            Operator::Pair,
+            Operator::Impair,
+            Operator::Lowest,
+            Operator::Highest,
+            Operator::SumBelow(0),
+            Operator::SumEquals(0),
+            Operator::SumAbove(0),
+            Operator::SumMultipleOf(0),
+            Operator::SumBetween(0, 0),
+            Operator::ProductBelow(0),
+            Operator::ProductEquals(0),
+            Operator::ProductAbove(0),
+            Operator::SameParity,
+            Operator::AlternatingParity,
+            Operator::MaxEquals(0),
+            Operator::MinEquals(0),
+        ];
+        for operator in operators {
+            assert!(
+                rules.iter().any(|rule| matches!(rule,
+                    Rule::MatchesOp(op, _) if discriminant(op) == discriminant(&operator))),
+                "no rule for operator {operator}"
+            );
+        }
+
+        assert!(rules.iter().any(|r| matches!(r, Rule::XColumnsEquals(..))));
+        assert!(rules.iter().any(|r| matches!(r, Rule::ColumnEquals(..))));
+        assert!(rules
+            .iter()
+            .any(|r| matches!(r, Rule::SetColumnsEqual { .. })));
+        assert!(rules
+            .iter()
+            .any(|r| matches!(r, Rule::ColumnCompare { .. })));
+        assert!(rules.iter().any(|r| matches!(r, Rule::DistinctValues(_))));
+        assert!(rules.iter().any(|r| matches!(r, Rule::AdjacentEquals(_))));
+        assert!(rules.iter().any(|r| matches!(r, Rule::Monotonic { .. })));
+
+        let filtered = rules.filter_by_difficulty(&gc, 50);
+        assert!(filtered.len() < rules.len());
+        assert!(filtered
+            .iter()
+            .all(|r| r.get_mask(&gc).unwrap().count_ones() * 100 / 125 > 50));
+    }
+
+    #[test]
+    fn test_product_thresholds() {
+        let max_threshold = |gc: &GameConfiguration, columns: &ColumnSet| {
+            gc.all_candidate_rules()
+                .unwrap()
+                .iter()
+                .filter_map(|rule| match rule {
+                    Rule::MatchesOp(Operator::ProductAbove(value), cs) if cs == columns => {
+                        Some(*value)
+                    }
+                    _ => None,
+                })
+                .max()
+        };
+
+        let gc = generate_game_configuration(5, 3, 0);
+        let ab: ColumnSet = HashSet::from([Column::from(0), Column::from(1)]).into();
+        let abc: ColumnSet =
+            HashSet::from([Column::from(0), Column::from(1), Column::from(2)]).into();
+        assert_eq!(max_threshold(&gc, &ab), Some(16));
+        assert_eq!(max_threshold(&gc, &abc), Some(64));
+
+        // 8 * 8 * 8 is past the largest threshold, which still tells such products apart
+        let gc = generate_game_configuration(9, 3, 0);
+        assert_eq!(max_threshold(&gc, &ab), Some(64));
+        assert_eq!(max_threshold(&gc, &abc), Some(u8::MAX));
+        assert!(Rule::MatchesOp(Operator::ProductAbove(u8::MAX), abc)
+            .evaluate(gc.parse_code("888").unwrap())
+            .unwrap());
+    }
+
+    #[test]
+    fn test_parse_code() {
+        let gc = generate_game_configuration(5, 3, 0);
+
+        assert_eq!(gc.parse_code("041").unwrap(), Code::new(vec![0, 4, 1]));
+        assert!(matches!(
+            gc.parse_code("04"),
+            Err(EnigmindError::WrongCodeLength {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            gc.parse_code("0412"),
+            Err(EnigmindError::WrongCodeLength {
+                expected: 3,
+                found: 4
+            })
+        ));
+        assert!(matches!(
+            gc.parse_code("051"),
+            Err(EnigmindError::DigitOutOfBounds(5))
+        ));
+        assert!(matches!(gc.parse_code(""), Err(EnigmindError::EmptyCode)));
+    }
+
+    #[test]
+    fn test_minimal_verificators() {
+        let gc = handcrafted_game(vec![]).configuration;
+        let verifier = |id, shifts: &[usize]| {
+            let mut mask = BitMask::zeros(gc.solution_count() as usize);
+            for shift in shifts {
+                mask.set(*shift, true).unwrap();
+            }
+            Verifier {
+                rule: Rule::DistinctValues(id),
+                mask,
+            }
+        };
+        // The two large masks alone isolate code 0, but they are the first the cleanup drops
+        let verificators = vec![
+            verifier(0, &[0, 1, 2, 3, 4, 5]),
+            verifier(1, &[0, 6, 7, 8]),
+            verifier(2, &[0, 1, 2]),
+            verifier(3, &[0, 1, 3]),
+            verifier(4, &[0, 2, 3]),
+        ];
+
+        let cleaned = remove_redundant_verificators(verificators.clone(), &gc);
+        let minimal = minimal_verificators(&verificators, &gc);
+
+        assert_eq!(cleaned.len(), 3);
+        assert_eq!(minimal.len(), 2);
+        let mut mask = BitMask::ones(gc.solution_count() as usize);
+        for v in minimal.iter() {
+            mask &= &v.mask;
+        }
+        assert_eq!(mask.trailing_zeros(), 0);
+        assert_eq!(mask.count_ones(), 1);
+    }
+
+    #[test]
+    fn test_generate_minimized_game() {
+        let game = generate_game(GenerationOptions {
+            min_difficulty: 20,
+            seed: Some(5),
+            minimize: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let default = generate_game_seeded(5, 3, 20, 5).unwrap();
+
+        assert_eq!(game.code, default.code);
+        assert!(game.criterias.len() <= default.criterias.len());
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+    }
+
+    #[test]
+    fn test_generation_options() {
+        let game = generate_game(GenerationOptions::default()).unwrap();
+        assert_eq!(game.configuration.base, 5);
+        assert_eq!(game.configuration.column_count, 3);
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+
+        let options = GenerationOptions {
+            base: 4,
+            column_count: 2,
+            min_difficulty: 30,
+            seed: Some(8),
+            max_criteria: Some(6),
+            minimize: true,
+            max_attempts: Some(12),
+            strategy: SelectionStrategy::MaxEntropy,
+            target_criteria: None,
+            max_rule_picks: Some(10_000),
+            difficulty_band: Some((1, 10)),
+            allowed_operators: BTreeSet::from(OperatorKind::ALL),
+            colored_logs: false,
+        };
+        let game = generate_game(options.clone()).unwrap();
+        assert_eq!(game.configuration.base, 4);
+        assert_eq!(game.configuration.column_count, 2);
+        assert_eq!(game.configuration.min_difficulty, 30);
+        assert_eq!(game.seed, 8);
+        assert_eq!(generate_game(options).unwrap().code, game.code);
+    }
+
+    #[test]
+    fn test_max_entropy_strategy() {
+        let verifier_count = |strategy| {
+            (0..10)
+                .map(|seed| {
+                    let game = generate_game(GenerationOptions {
+                        seed: Some(seed),
+                        strategy,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                    assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+                    game.criterias.len()
+                })
+                .sum::<usize>()
+        };
+
+        assert!(
+            verifier_count(SelectionStrategy::MaxEntropy)
+                < verifier_count(SelectionStrategy::RandomGreedy)
+        );
+    }
+
+    #[test]
+    fn test_target_criteria() {
+        for seed in 0..5 {
+            let game = generate_game(GenerationOptions {
+                seed: Some(seed),
+                target_criteria: Some(6),
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert_eq!(game.criterias.len(), 6);
+            assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+            for crit in game.criterias.iter() {
+                for other in game.criterias.iter().filter(|other| other.id != crit.id) {
+                    assert!(&crit.verif.mask & &other.verif.mask != crit.verif.mask);
+                }
+            }
+        }
+
+        let game = generate_game(GenerationOptions {
+            seed: Some(2),
+            target_criteria: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(game.criterias.len(), 2);
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+
+        // Every rule keeps over 20% of the codes, none can leave a single one
+        assert!(matches!(
+            generate_game(GenerationOptions {
+                min_difficulty: 20,
+                target_criteria: Some(1),
+                ..Default::default()
+            }),
+            Err(EnigmindError::CannotSatisfyConstraints)
+        ));
+    }
+
+    #[test]
+    fn test_rule_pick_limits() {
+        let gc = generate_game_configuration(3, 2, 0);
+        let verifier = |rule| Verifier::new(&gc, rule).unwrap();
+        let options = GenerationOptions {
+            max_rule_picks: Some(1),
+            ..Default::default()
+        };
+
+        // Two rules are needed, one pick isn't enough
+        let candidates: Verificators = vec![
+            verifier(Rule::ColumnEquals(Column::from(0), 1)),
+            verifier(Rule::ColumnEquals(Column::from(1), 2)),
+        ]
+        .into();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(matches!(
+            generate_verificators(&candidates, &gc, &options, &mut rng),
+            Err(EnigmindError::GenerationTimeout)
+        ));
+        assert!(
+            generate_verificators(&candidates, &gc, &GenerationOptions::default(), &mut rng)
+                .is_ok()
+        );
+
+        // Once the only rule is picked, no other one can narrow the codes
+        let candidates: Verificators =
+            vec![verifier(Rule::ColumnEquals(Column::from(0), 1))].into();
+        assert!(matches!(
+            generate_verificators(&candidates, &gc, &GenerationOptions::default(), &mut rng),
+            Err(EnigmindError::NoUniqueSolution)
+        ));
+    }
+
+    #[test]
+    fn test_difficulty_band() {
+        let band_game = |seed, band| {
+            generate_game(GenerationOptions {
+                seed: Some(seed),
+                difficulty_band: Some(band),
+                ..Default::default()
+            })
+        };
+
+        for seed in 0..3 {
+            let easy = band_game(seed, (1, 2)).unwrap();
+            assert!(easy.estimated_difficulty() <= 2);
+            assert_eq!(easy.consistent_codes(), vec![easy.code.clone()]);
+
+            let hard = band_game(seed, (4, u32::MAX)).unwrap();
+            assert!(hard.estimated_difficulty() >= 4);
+            assert_eq!(hard.consistent_codes(), vec![hard.code.clone()]);
+        }
+
+        assert!(matches!(
+            band_game(0, (20, 30)),
+            Err(EnigmindError::CannotSatisfyConstraints)
+        ));
+    }
+
+    #[test]
+    fn test_allowed_operators() {
+        let allowing = |seed, allowed: &[OperatorKind]| {
+            generate_game(GenerationOptions {
+                seed: Some(seed),
+                allowed_operators: allowed.iter().copied().collect(),
+                ..Default::default()
+            })
+        };
+
+        for seed in 0..3 {
+            let game = allowing(seed, &[OperatorKind::Sums]).unwrap();
+            assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+            for crit in game.criterias.iter() {
+                assert_eq!(
+                    crit.verif.rule.kinds(),
+                    BTreeSet::from([OperatorKind::Sums])
+                );
+            }
+        }
+
+        // Parity can't tell apart codes with the same odd digits
+        assert!(matches!(
+            allowing(0, &[OperatorKind::Parity]),
+            Err(EnigmindError::NoUniqueSolution)
+        ));
+        assert!(matches!(
+            allowing(0, &[]),
+            Err(EnigmindError::NoRulesAvailable)
+        ));
+    }
+
+    #[test]
+    fn test_max_criteria() {
+        for seed in 0..5 {
+            let game = generate_game(GenerationOptions {
+                seed: Some(seed),
+                max_criteria: Some(4),
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert!(game.criterias.len() <= 4);
+            assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+        }
+
+        assert!(matches!(
+            generate_game(GenerationOptions {
+                max_criteria: Some(0),
+                ..Default::default()
+            }),
+            Err(EnigmindError::CannotSatisfyConstraints)
+        ));
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let game = generate_game(GenerationOptions {
+            seed: Some(3),
+            max_attempts: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(game.configuration.max_attempts, Some(3));
+        assert!(!game.configuration.is_lost(3));
+        assert!(game.configuration.is_lost(4));
+
+        let unlimited = generate_game_seeded(5, 3, 0, 3).unwrap();
+        assert_eq!(unlimited.code, game.code);
+        assert!(!unlimited.configuration.is_lost(u32::MAX));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_verifiers() {
+        for (base, column_count) in [(5, 3), (4, 4), (3, 5)] {
+            let gc = generate_game_configuration(base, column_count, 0);
+            let rules = gc.all_candidate_rules().unwrap();
+
+            let parallel = super::parallel_verifiers(&gc, &rules).unwrap();
+            let sequential = super::sequential_verifiers(&gc, &rules).unwrap();
+            assert_eq!(parallel.len(), rules.len());
+            assert!(parallel == sequential);
+        }
+    }
+
+    #[test]
+    fn test_from_rules() {
+        let gc = generate_game_configuration(5, 3, 0);
+        let rules: Rules = vec![
+            Rule::ColumnEquals(Column::from(0), 1),
+            Rule::ColumnCompare {
+                left: Column::from(0),
+                right: Column::from(1),
+                ordering: Ordering::Less,
+            },
+            Rule::ColumnCompare {
+                left: Column::from(1),
+                right: Column::from(2),
+                ordering: Ordering::Less,
+            },
+            Rule::MatchesOp(Operator::SumEquals(6), columns(&[0, 1, 2])),
+        ]
+        .into();
+
+        let game = Game::from_rules(gc.clone(), rules.clone()).unwrap();
+        assert_eq!(game.code, Code::new(vec![1, 2, 3]));
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+        for (id, (crit, rule)) in game.criterias.iter().zip(rules.iter()).enumerate() {
+            assert_eq!(crit.id as usize, id);
+            assert!(crit.verif.rule == *rule);
+            assert!(crit.rules.contains(rule));
+        }
+        let descriptions: HashSet<&String> = game
+            .criterias
+            .iter()
+            .map(|crit| &crit.description)
+            .collect();
+        assert_eq!(descriptions.len(), game.criterias.len());
+
+        let under_constrained: Rules = rules.iter().take(2).cloned().collect();
+        assert!(matches!(
+            Game::from_rules(gc.clone(), under_constrained),
+            Err(EnigmindError::NoUniqueSolution)
+        ));
+        let contradictory: Rules = vec![
+            Rule::ColumnEquals(Column::from(0), 1),
+            Rule::ColumnEquals(Column::from(0), 2),
+        ]
+        .into();
+        assert!(matches!(
+            Game::from_rules(gc, contradictory),
+            Err(EnigmindError::NoUniqueSolution)
+        ));
+    }
+
+    #[test]
+    fn test_replay() {
+        for seed in 0..3 {
+            let game = generate_game(GenerationOptions {
+                min_difficulty: 20,
+                seed: Some(seed),
+                max_attempts: Some(10),
+                ..Default::default()
+            })
+            .unwrap();
+            let replayed = Game::replay(&game.configuration, game.seed).unwrap();
+
+            assert_eq!(
+                serde_json::to_string(&replayed).unwrap(),
+                serde_json::to_string(&game).unwrap()
+            );
+        }
+
+        let configuration = GameConfiguration {
+            schema_version: SCHEMA_VERSION - 1,
+            ..generate_game_configuration(5, 3, 0)
+        };
+        assert!(matches!(
+            Game::replay(&configuration, 0),
+            Err(EnigmindError::UnsupportedSchemaVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_seeded_game_is_stable() {
+        // Shared seeds must keep giving the same puzzle, whatever the generation code becomes
+        let game = generate_game_seeded(5, 3, 0, 42).unwrap();
+        let criterias: Vec<(String, &str)> = game
+            .criterias
+            .iter()
+            .map(|crit| (crit.verif.rule.to_string(), crit.description.as_str()))
+            .collect();
+
+        assert_eq!(game.code, Code::new(vec![4, 3, 1]));
+        assert_eq!(
+            criterias,
+            vec![
+                (
+                    "SumEquals([A, B], 7)".to_string(),
+                    "2 columns have a sum equal to 7"
+                ),
+                (
+                    "SumMultipleOf([A, B, C], 4)".to_string(),
+                    "Column(s) [A, B, C] sum is a multiple of X"
+                ),
+                (
+                    "SumMultipleOf([B], 3)".to_string(),
+                    "The sum of 1 columns is a multiple of 3"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_with_options() {
+        for seed in 0..3 {
+            let game = generate_game(GenerationOptions {
+                seed: Some(seed),
+                minimize: true,
+                strategy: SelectionStrategy::MaxEntropy,
+                max_rule_picks: Some(10_000),
+                allowed_operators: BTreeSet::from([
+                    OperatorKind::Sums,
+                    OperatorKind::Columns,
+                    OperatorKind::Parity,
+                ]),
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(game.configuration.generation.is_some());
+
+            // Replayed from the configuration as a client would have stored it
+            let json = serde_json::to_string(&game).unwrap();
+            let stored: Game = serde_json::from_str(&json).unwrap();
+            let replayed = Game::replay(&stored.configuration, stored.seed).unwrap();
+            assert_eq!(serde_json::to_string(&replayed).unwrap(), json);
+        }
+
+        let game = generate_game_seeded(5, 3, 0, 1).unwrap();
+        assert!(game.configuration.generation.is_none());
+        assert!(!serde_json::to_string(&game).unwrap().contains("generation"));
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret(5, 3, Some(7)).unwrap();
+
+        assert!(secret.is_well_formed(&generate_game_configuration(5, 3, 0)));
+        assert_eq!(generate_secret(5, 3, Some(7)).unwrap(), secret);
+        assert!(generate_secret(5, 3, None).is_ok());
+        assert!(matches!(
+            generate_secret(1, 3, Some(7)),
+            Err(EnigmindError::InvalidConfiguration { .. })
+        ));
+    }
+
+    #[test]
+    fn test_checked_solution_count() {
+        let gc = |base, column_count| test_configuration(column_count, base);
+
+        assert_eq!(
+            gc(10, 4)
+                .checked_solution_count(MAX_SOLUTION_COUNT)
+                .unwrap(),
+            10_000
+        );
+        assert!(matches!(
+            gc(10, 5).checked_solution_count(MAX_SOLUTION_COUNT),
+            Err(EnigmindError::ConfigurationTooLarge)
+        ));
+        assert!(matches!(
+            gc(200, 10).checked_solution_count(u32::MAX),
+            Err(EnigmindError::ConfigurationTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_generate_invalid_configuration() {
+        for (base, column_count) in [(0, 3), (1, 3), (5, 0), (5, 6), (10, 2), (200, 10)] {
+            assert!(matches!(
+                generate_game_simple(base, column_count, 0),
+                Err(EnigmindError::InvalidConfiguration { base: b, column_count: c })
+                    if b == base && c == column_count
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_largest_configuration() {
+        assert!(generate_game_configuration(9, 4, 0).validate().is_ok());
+        assert!(generate_game_configuration(MAX_BASE + 1, 2, 0)
+            .validate()
+            .is_err());
+        assert!(generate_game_configuration(2, 13, 0).validate().is_ok());
+        assert!(generate_game_configuration(2, 14, 0).validate().is_err());
+        assert_eq!(
+            generate_game_configuration(200, 10, 0).solution_count(),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn test_largest_mask() {
+        let gc = test_configuration(4, MAX_BASE);
+        let last_code = Code::new(vec![8, 8, 8, 8]);
+        let all_eights = (0..4)
+            .map(|i| Rule::ColumnEquals(Column::from(i), 8))
+            .reduce(|left, right| Rule::And(Box::new(left), Box::new(right)))
+            .unwrap();
+        let sum = Rule::MatchesOp(Operator::SumEquals(32), columns(&[0, 1, 2, 3]));
+
+        assert_eq!(gc.solution_count(), 6561);
+        assert!(test_configuration(5, MAX_BASE).validate().is_err());
+        for rule in [all_eights, sum] {
+            let mask = rule.get_mask(&gc).unwrap();
+            assert_eq!(mask.count_ones(), 1);
+            assert_eq!(mask.trailing_zeros() as u32, last_code.get_shift(&gc));
+        }
+        assert_eq!(
+            Rule::ColumnEquals(Column::from(3), 8)
+                .get_mask(&gc)
+                .unwrap()
+                .count_ones(),
+            729
+        );
+
+        // Sums alone keep the rule count, and so the test, short at this size
+        let game = generate_game(GenerationOptions {
+            base: MAX_BASE,
+            column_count: 4,
+            seed: Some(1),
+            allowed_operators: BTreeSet::from([OperatorKind::Sums]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+    }
+
+    #[test]
+    fn test_estimated_difficulty() {
+        let redundant = handcrafted_game(vec![
+            Rule::MatchesOp(Operator::Pair, columns(&[0])),
+            Rule::MatchesOp(Operator::SumEquals(0), columns(&[0, 1])),
+            Rule::MatchesOp(Operator::Pair, columns(&[1])),
+        ]);
+        let coupled = handcrafted_game(vec![
+            Rule::ColumnCompare {
+                left: Column::from(0),
+                right: Column::from(1),
+                ordering: Ordering::Equal,
+            },
+            Rule::MatchesOp(Operator::SumBelow(1), columns(&[0])),
+        ]);
+
+        assert_eq!(redundant.consistent_codes().len(), 1);
+        assert_eq!(coupled.consistent_codes().len(), 1);
+        assert_eq!(redundant.estimated_difficulty(), 1);
+        assert_eq!(coupled.estimated_difficulty(), 2);
+    }
+
+    #[test]
+    fn test_to_column_index() {
+        let game = generate_game_seeded(5, 3, 0, 1).unwrap();
+
+        assert_eq!(game.to_column_index('A'), Some(0));
+        assert_eq!(game.to_column_index('c'), Some(2));
+        assert_eq!(game.to_column_index('C'), Some(2));
+        assert_eq!(game.to_column_index('@'), None);
+        assert_eq!(game.to_column_index('D'), None);
+        assert_eq!(game.to_column_index('1'), None);
+        assert_eq!(game.to_column_index('é'), None);
+        assert!(game.is_column_compatible('b'));
+        assert!(!game.is_column_compatible('@'));
+    }
+
+    #[test]
+    fn test_unique_descriptions() {
+        for seed in 0..20 {
+            let game = generate_game_seeded(5, 3, 0, seed).unwrap();
+            let descriptions: HashSet<&String> = game
+                .criterias
+                .iter()
+                .map(|crit| &crit.description)
+                .collect();
+            assert_eq!(descriptions.len(), game.criterias.len());
+        }
+    }
+
+    #[test]
+    fn test_generate_without_rules() {
+        assert!(matches!(
+            generate_game_simple(5, 3, 100),
+            Err(EnigmindError::NoRulesAvailable)
+        ));
+    }
 }
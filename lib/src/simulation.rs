@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rule::{Operator, Rule},
+    setup::{generate_game, GenerationOptions},
+};
+
+/// Aggregated figures over many generated games, to tune the difficulty filter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DifficultyStats {
+    pub games: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    pub mean_criteria: f64,
+    pub median_criteria: f64,
+    pub mean_estimated_difficulty: f64,
+    /// Number of verifiers of each kind of rule
+    pub rule_kinds: BTreeMap<String, usize>,
+}
+
+fn rule_kind(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::MatchesOp(op, _) => match op {
+            Operator::Pair => "Pair",
+            Operator::Impair => "Impair",
+            Operator::Lowest => "Lowest",
+            Operator::Highest => "Highest",
+            Operator::SumBelow(_) => "SumBelow",
+            Operator::SumEquals(_) => "SumEquals",
+            Operator::SumAbove(_) => "SumAbove",
+            Operator::SumMultipleOf(_) => "SumMultipleOf",
+            Operator::SumBetween(..) => "SumBetween",
+            Operator::ProductBelow(_) => "ProductBelow",
+            Operator::ProductEquals(_) => "ProductEquals",
+            Operator::ProductAbove(_) => "ProductAbove",
+            Operator::SameParity => "SameParity",
+            Operator::AlternatingParity => "AlternatingParity",
+            Operator::MaxEquals(_) => "MaxEquals",
+            Operator::MinEquals(_) => "MinEquals",
+        },
+        Rule::XColumnsEquals(..) => "XColumnsEquals",
+        Rule::SetColumnsEqual { .. } => "SetColumnsEqual",
+        Rule::ColumnEquals(..) => "ColumnEquals",
+        Rule::ColumnCompare { .. } => "ColumnCompare",
+        Rule::DistinctValues(_) => "DistinctValues",
+        Rule::AdjacentEquals(_) => "AdjacentEquals",
+        Rule::Monotonic { .. } => "Monotonic",
+        Rule::Not(_) => "Not",
+        Rule::And(..) => "And",
+        Rule::Or(..) => "Or",
+    }
+}
+
+/// Generates `n` games with the given options and aggregates their statistics.
+/// With a seed, game `i` uses seed + i so that runs are reproducible.
+pub fn simulate_games(options: &GenerationOptions, n: usize) -> DifficultyStats {
+    let mut stats = DifficultyStats {
+        games: n,
+        ..Default::default()
+    };
+    let mut criteria_counts = Vec::new();
+    let mut total_difficulty = 0;
+
+    for i in 0..n {
+        let game = generate_game(GenerationOptions {
+            seed: options.seed.map(|seed| seed.wrapping_add(i as u64)),
+            ..options.clone()
+        });
+
+        match game {
+            Ok(game) => {
+                criteria_counts.push(game.criterias.len());
+                total_difficulty += game.estimated_difficulty();
+                for crit in game.criterias.iter() {
+                    *stats
+                        .rule_kinds
+                        .entry(rule_kind(&crit.verif.rule).to_string())
+                        .or_default() += 1;
+                }
+            }
+            Err(_) => stats.failures += 1,
+        }
+    }
+
+    if n > 0 {
+        stats.failure_rate = stats.failures as f64 / n as f64;
+    }
+    if !criteria_counts.is_empty() {
+        let generated = criteria_counts.len() as f64;
+        stats.mean_criteria = criteria_counts.iter().sum::<usize>() as f64 / generated;
+        stats.mean_estimated_difficulty = total_difficulty as f64 / generated;
+
+        criteria_counts.sort_unstable();
+        let middle = criteria_counts.len() / 2;
+        stats.median_criteria = match criteria_counts.len() % 2 {
+            0 => (criteria_counts[middle - 1] + criteria_counts[middle]) as f64 / 2.0,
+            _ => criteria_counts[middle] as f64,
+        };
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simulate_games;
+    use crate::setup::GenerationOptions;
+
+    #[test]
+    fn test_simulate_games() {
+        let options = GenerationOptions {
+            seed: Some(1),
+            ..Default::default()
+        };
+        let stats = simulate_games(&options, 6);
+
+        assert_eq!(stats.games, 6);
+        assert_eq!(stats.failures, 0);
+        assert_eq!(stats.failure_rate, 0.0);
+        assert!((2.0..=10.0).contains(&stats.mean_criteria));
+        assert!((2.0..=10.0).contains(&stats.median_criteria));
+        assert!(stats.mean_estimated_difficulty > 0.0);
+        assert_eq!(
+            stats.rule_kinds.values().sum::<usize>() as f64,
+            stats.mean_criteria * 6.0
+        );
+
+        let again = simulate_games(&options, 6);
+        assert_eq!(again.rule_kinds, stats.rule_kinds);
+    }
+}
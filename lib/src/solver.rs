@@ -0,0 +1,274 @@
+use nbitmask::BitMask;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    code::Code,
+    criteria::mask_codes,
+    error::EnigmindError,
+    setup::{Game, GameConfiguration},
+};
+
+/// What a player learnt testing a code against a criteria.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    pub code: Code,
+    /// Criteria id
+    pub criterion: u8,
+    pub passed: bool,
+}
+
+impl TestResult {
+    /// Index of the code among the game codes and of the criteria among its criterias,
+    /// failing when either isn't part of the game.
+    fn indexes(&self, game: &Game) -> Result<(usize, usize), EnigmindError> {
+        if !self.code.is_well_formed(&game.configuration) {
+            return Err(EnigmindError::InvalidTestResult(format!(
+                "code {} doesn't fit the game",
+                self.code
+            )));
+        }
+        let crit = game
+            .criterias
+            .iter()
+            .position(|crit| crit.id == self.criterion)
+            .ok_or_else(|| {
+                EnigmindError::InvalidTestResult(format!("unknown criteria {}", self.criterion))
+            })?;
+        Ok((self.code.get_shift(&game.configuration) as usize, crit))
+    }
+}
+
+/// Mask of the codes holding only the code at `code_index`.
+fn code_mask(len: usize, code_index: usize) -> BitMask<u64> {
+    let mut mask = BitMask::zeros(len);
+    // Out of range indexes leave the mask empty, accepted by no rule
+    let _ = mask.set(code_index, true);
+    mask
+}
+
+/// Whether a rule mask accepts the code of a `code_mask`.
+fn accepts(rule_mask: &BitMask<u64>, code: &BitMask<u64>) -> bool {
+    (rule_mask & code).count_ones() > 0
+}
+
+/// What a player knows of a game from the criteria and the results of their tests: which
+/// rules of each criteria may still be its verifier.
+struct Knowledge {
+    codes: Vec<Code>,
+    // Per criteria and rule, the codes it accepts
+    rules: Vec<Vec<BitMask<u64>>>,
+    possible: Vec<Vec<bool>>,
+}
+
+impl Knowledge {
+    fn new(game: &Game) -> Self {
+        let gc = &game.configuration;
+        let codes: Vec<Code> = gc.codes().collect();
+
+        let rules: Vec<Vec<BitMask<u64>>> = game
+            .criterias
+            .iter()
+            .map(|crit| match crit.candidate_masks(gc) {
+                Ok(masks) => masks.into_iter().map(|(_, mask)| mask).collect(),
+                Err(_) => vec![BitMask::zeros(codes.len()); crit.rules.len()],
+            })
+            .collect();
+        let possible = rules.iter().map(|rules| vec![true; rules.len()]).collect();
+
+        Self {
+            codes,
+            rules,
+            possible,
+        }
+    }
+
+    /// Codes accepted by a possible rule of the criteria, among those `keep` selects.
+    fn accepted(&self, crit: usize, keep: impl Fn(&BitMask<u64>) -> bool) -> BitMask<u64> {
+        let mut mask = BitMask::zeros(self.codes.len());
+        for (rule, rule_mask) in self.rules[crit].iter().enumerate() {
+            if self.possible[crit][rule] && keep(rule_mask) {
+                mask = &mask | rule_mask;
+            }
+        }
+        mask
+    }
+
+    /// Codes every criteria may accept, leaving out the `skipped` one.
+    fn candidates(&self, skipped: Option<usize>) -> BitMask<u64> {
+        let mut mask = BitMask::ones(self.codes.len());
+        for crit in 0..self.rules.len() {
+            if Some(crit) != skipped {
+                mask &= &self.accepted(crit, |_| true);
+            }
+        }
+        mask
+    }
+
+    fn learn(&mut self, code_index: usize, crit: usize, result: bool) {
+        let code = code_mask(self.codes.len(), code_index);
+        for (possible, rule_mask) in self.possible[crit].iter_mut().zip(&self.rules[crit]) {
+            *possible &= accepts(rule_mask, &code) == result;
+        }
+    }
+
+    /// Candidates left if testing the code of a `code_mask` against the criteria turns out
+    /// right or wrong.
+    fn outcomes(&self, others: &BitMask<u64>, crit: usize, code: &BitMask<u64>) -> (usize, usize) {
+        let right = (others & &self.accepted(crit, |mask| accepts(mask, code))).count_ones();
+        let wrong = (others & &self.accepted(crit, |mask| !accepts(mask, code))).count_ones();
+        (right, wrong)
+    }
+
+    /// Test, as (code index, criteria), whose outcome is the most uncertain among those
+    /// making progress whatever it is, or with either outcome unless `sure_progress`.
+    /// Tests whose outcome is already known are left out.
+    fn most_informative(&self, total: usize, sure_progress: bool) -> Option<(usize, usize)> {
+        let mut best: Option<(f64, usize, usize)> = None;
+        for crit in 0..self.rules.len() {
+            let others = self.candidates(Some(crit));
+            for code_index in 0..self.codes.len() {
+                let code = code_mask(self.codes.len(), code_index);
+                let (right, wrong) = self.outcomes(&others, crit, &code);
+                let progress = match sure_progress {
+                    true => right < total && wrong < total,
+                    false => right < total || wrong < total,
+                };
+                if right == 0 || wrong == 0 || !progress {
+                    continue;
+                }
+
+                let p = right as f64 / (right + wrong) as f64;
+                let entropy = -[p, 1.0 - p]
+                    .iter()
+                    .filter(|p| **p > 0.0)
+                    .map(|p| p * p.log2())
+                    .sum::<f64>();
+                if best.is_none_or(|(score, ..)| entropy > score) {
+                    best = Some((entropy, code_index, crit));
+                }
+            }
+        }
+        best.map(|(_, code_index, crit)| (code_index, crit))
+    }
+
+    fn candidate_codes(&self, gc: &GameConfiguration) -> Vec<Code> {
+        mask_codes(self.candidates(None), gc)
+    }
+}
+
+impl Game {
+    /// Codes a player can't rule out yet after the given tests, as (code, criteria id).
+    /// Fails on a test whose code or criteria isn't part of the game.
+    pub fn remaining_candidates(&self, tests: &[(Code, u8)]) -> Result<Vec<Code>, EnigmindError> {
+        let results = tests
+            .iter()
+            .map(|(code, id)| {
+                let crit = self.criterion(*id).ok_or_else(|| {
+                    EnigmindError::InvalidTestResult(format!("unknown criteria {id}"))
+                })?;
+                Ok(TestResult {
+                    code: code.clone(),
+                    criterion: *id,
+                    passed: crit.verif.rule.evaluate(code.clone())?,
+                })
+            })
+            .collect::<Result<Vec<TestResult>, EnigmindError>>()?;
+        self.candidates_after(&results)
+    }
+
+    /// Same as `remaining_candidates`, from results the player already knows, so nothing is
+    /// learnt from the verifiers.
+    /// Fails on a result whose code or criteria isn't part of the game.
+    pub fn candidates_after(&self, results: &[TestResult]) -> Result<Vec<Code>, EnigmindError> {
+        Ok(self
+            .knowledge_after(results)?
+            .candidate_codes(&self.configuration))
+    }
+
+    fn knowledge_after(&self, results: &[TestResult]) -> Result<Knowledge, EnigmindError> {
+        let mut knowledge = Knowledge::new(self);
+        for result in results {
+            let (code_index, crit) = result.indexes(self)?;
+            knowledge.learn(code_index, crit, result.passed);
+        }
+        Ok(knowledge)
+    }
+
+    /// Next test to make after the known results, as (code, criteria id), the one whose
+    /// outcome is the most uncertain, preferring those making progress whatever it is.
+    /// None once a single code is left, or when every test either has a known outcome or
+    /// can't narrow the codes.
+    /// Fails on a known result whose code or criteria isn't part of the game.
+    pub fn recommend_test(
+        &self,
+        known: &[TestResult],
+    ) -> Result<Option<(Code, u8)>, EnigmindError> {
+        let knowledge = self.knowledge_after(known)?;
+        let total = knowledge.candidates(None).count_ones();
+        if total <= 1 {
+            return Ok(None);
+        }
+
+        Ok(knowledge
+            .most_informative(total, true)
+            .or_else(|| knowledge.most_informative(total, false))
+            .map(|(code_index, crit)| {
+                (knowledge.codes[code_index].clone(), self.criterias[crit].id)
+            }))
+    }
+
+    /// Tests, as (code, criteria id), a player knowing only the criteria would make to find
+    /// the code, each time picking the one whose outcome is the most uncertain.
+    /// Far heavier than `estimated_difficulty`, every code being tried against every criteria
+    /// at each step.
+    pub fn optimal_solution_path(&self) -> Vec<(Code, u8)> {
+        let mut knowledge = Knowledge::new(self);
+        let results: Vec<Vec<bool>> = self
+            .criterias
+            .iter()
+            .map(|crit| {
+                knowledge
+                    .codes
+                    .iter()
+                    .map(|code| crit.verif.rule.evaluate(code.clone()).unwrap_or(false))
+                    .collect()
+            })
+            .collect();
+
+        let mut path = Vec::new();
+        loop {
+            let total = knowledge.candidates(None).count_ones();
+            if total <= 1 {
+                break;
+            }
+
+            // Without a test making progress whatever its outcome, the one making the most
+            // progress with its actual outcome
+            let test = knowledge.most_informative(total, true).or_else(|| {
+                let mut fallback: Option<(usize, usize, usize)> = None;
+                for (crit, crit_results) in results.iter().enumerate() {
+                    let others = knowledge.candidates(Some(crit));
+                    for (code_index, result) in crit_results.iter().enumerate() {
+                        let code = code_mask(knowledge.codes.len(), code_index);
+                        let (right, wrong) = knowledge.outcomes(&others, crit, &code);
+                        let remaining = if *result { right } else { wrong };
+                        if remaining < total
+                            && fallback.is_none_or(|(fewest, ..)| remaining < fewest)
+                        {
+                            fallback = Some((remaining, code_index, crit));
+                        }
+                    }
+                }
+                fallback.map(|(_, code_index, crit)| (code_index, crit))
+            });
+
+            let Some((code_index, crit)) = test else {
+                break;
+            };
+            knowledge.learn(code_index, crit, results[crit][code_index]);
+            path.push((knowledge.codes[code_index].clone(), self.criterias[crit].id));
+        }
+
+        path
+    }
+}
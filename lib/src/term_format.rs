@@ -4,10 +4,54 @@ use crate::{
     verifier::{Verificators, Verifier},
 };
 use pad::PadStr;
-use std::ops::Deref;
+
+const KEYWORD: &str = "\x1b[36m";
+const COLUMN: &str = "\x1b[33m";
+const THRESHOLD: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
 
 pub trait TermFormat {
     fn formatted(&self) -> String;
+
+    /// Same text as `formatted`, with operators, columns and thresholds highlighted by ANSI escapes.
+    fn formatted_colored(&self) -> String;
+
+    /// Colored or plain as the caller asks, only it knowing whether its output is a terminal.
+    fn formatted_with(&self, colored: bool) -> String {
+        if colored {
+            self.formatted_colored()
+        } else {
+            self.formatted()
+        }
+    }
+}
+
+/// Highlights words as operators, single capitals as columns and numbers as thresholds.
+fn colorize(text: &str) -> String {
+    let mut s = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut token = c.to_string();
+        if c.is_ascii_digit() {
+            while let Some(next) = chars.next_if(char::is_ascii_digit) {
+                token.push(next);
+            }
+            s.push_str(&format!("{THRESHOLD}{token}{RESET}"));
+        } else if c.is_ascii_alphabetic() {
+            while let Some(next) = chars.next_if(char::is_ascii_alphabetic) {
+                token.push(next);
+            }
+            let color = if token.len() == 1 && c.is_ascii_uppercase() {
+                COLUMN
+            } else {
+                KEYWORD
+            };
+            s.push_str(&format!("{color}{token}{RESET}"));
+        } else {
+            s.push(c);
+        }
+    }
+    s
 }
 
 impl TermFormat for Rule {
@@ -15,17 +59,31 @@ impl TermFormat for Rule {
         self.to_string()
             .pad_to_width_with_alignment(25, pad::Alignment::Left)
     }
+
+    fn formatted_colored(&self) -> String {
+        // Padded before coloring, escapes taking no room on screen
+        colorize(&self.formatted())
+    }
 }
 
 impl TermFormat for Rules {
     fn formatted(&self) -> String {
         let mut s = String::new();
-        for r in self.deref() {
+        for r in self.iter() {
             s.push_str(&r.formatted());
             s.push('\n');
         }
         s
     }
+
+    fn formatted_colored(&self) -> String {
+        let mut s = String::new();
+        for r in self.iter() {
+            s.push_str(&r.formatted_colored());
+            s.push('\n');
+        }
+        s
+    }
 }
 
 impl TermFormat for Verifier {
@@ -41,14 +99,76 @@ impl TermFormat for Verifier {
 
         s
     }
+
+    fn formatted_colored(&self) -> String {
+        // Only the rule is highlighted, the mask and its count are left plain
+        let plain = self.rule.formatted();
+        let mut s = self.rule.formatted_colored();
+        s.push_str(&self.formatted()[plain.len()..]);
+        s
+    }
 }
 
 impl TermFormat for Verificators {
     fn formatted(&self) -> String {
         let mut s = String::new();
-        for v in self.deref() {
+        for v in self.iter() {
             s.push_str(&v.formatted());
         }
         s
     }
+
+    fn formatted_colored(&self) -> String {
+        let mut s = String::new();
+        for v in self.iter() {
+            s.push_str(&v.formatted_colored());
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TermFormat;
+    use crate::{
+        column::Column, rule::Rule, rules::Rules, setup::test_configuration, verifier::Verifier,
+    };
+
+    fn strip_escapes(text: &str) -> String {
+        let mut s = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                s.push(c);
+            }
+        }
+        s
+    }
+
+    #[test]
+    fn test_formatted_colored() {
+        let rules = Rules::from(vec![
+            Rule::ColumnEquals(Column::from(0), 3),
+            Rule::ColumnCompare {
+                left: Column::from(1),
+                right: Column::from(2),
+                ordering: std::cmp::Ordering::Less,
+            },
+        ]);
+
+        let colored = rules.formatted_colored();
+        assert!(colored.contains("\x1b[33mA\x1b[0m"));
+        assert!(colored.contains("\x1b[35m3\x1b[0m"));
+        assert_eq!(strip_escapes(&colored), rules.formatted());
+        assert_eq!(rules.formatted_with(true), colored);
+        assert_eq!(rules.formatted_with(false), rules.formatted());
+
+        let gc = test_configuration(3, 5);
+        let verifier = Verifier::new(&gc, Rule::ColumnEquals(Column::from(0), 3)).unwrap();
+        let colored = verifier.formatted_colored();
+        assert!(colored.contains('\x1b'));
+        assert_eq!(strip_escapes(&colored), verifier.formatted());
+    }
 }
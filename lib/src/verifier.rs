@@ -1,7 +1,7 @@
 use crate::{error::EnigmindError, rule::Rule, setup::GameConfiguration};
 use nbitmask::BitMask;
 use serde::{Deserialize, Serialize};
-use std::{fmt, ops::Deref};
+use std::{collections::HashMap, fmt, ops::Deref};
 
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Verifier {
@@ -14,6 +14,21 @@ impl Verifier {
         let mask = rule.get_mask(gc)?;
         Ok(Self { rule, mask })
     }
+
+    /// Satisfiable and accepting more than `min_pct` percent of the possible codes.
+    pub fn is_within_difficulty(&self, gc: &GameConfiguration, min_pct: u8) -> bool {
+        let ones_count = self.mask.count_ones();
+        let difficulty = ones_count * 100 / gc.solution_count() as usize;
+        ones_count > 0 && difficulty > min_pct as usize
+    }
+
+    /// How many bits of information learning the rule gives, 0 for an unsatisfiable rule.
+    pub fn info_bits(&self, gc: &GameConfiguration) -> f64 {
+        match self.mask.count_ones() {
+            0 => 0.0,
+            ones_count => (gc.solution_count() as f64 / ones_count as f64).log2(),
+        }
+    }
 }
 
 impl fmt::Display for Verifier {
@@ -37,6 +52,46 @@ impl From<Vec<Verifier>> for Verificators {
     }
 }
 
+impl Verificators {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Verifier> {
+        self.0.get(index)
+    }
+
+    /// Keeps the first of the verifiers accepting exactly the same codes.
+    pub fn dedup(&mut self) {
+        let mut masks = MaskSet::default();
+        self.0.retain(|verif| masks.insert(&verif.mask));
+    }
+}
+
+/// Masks seen so far, keyed by their number of set bits and lowest one, so that a mask is
+/// only compared with the few sharing both.
+#[derive(Default)]
+pub(crate) struct MaskSet(HashMap<(usize, usize), Vec<BitMask<u64>>>);
+
+impl MaskSet {
+    /// Whether the mask wasn't seen yet, remembering it.
+    pub(crate) fn insert(&mut self, mask: &BitMask<u64>) -> bool {
+        let seen = self
+            .0
+            .entry((mask.count_ones(), mask.trailing_zeros()))
+            .or_default();
+        if seen.contains(mask) {
+            return false;
+        }
+        seen.push(mask.clone());
+        true
+    }
+}
+
 impl Deref for Verificators {
     type Target = Vec<Verifier>;
 
@@ -53,3 +108,65 @@ impl fmt::Display for Verificators {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Verificators, Verifier};
+    use crate::{
+        column::Column,
+        rule::{Operator, Rule},
+        setup::test_configuration,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_serde_keeps_the_mask() {
+        // 125 codes, so the mask doesn't fill its last word
+        let gc = test_configuration(3, 5);
+        let verif = Verifier::new(&gc, Rule::ColumnEquals(Column::from(0), 2)).unwrap();
+
+        let json = serde_json::to_string(&verif).unwrap();
+        let other: Verifier = serde_json::from_str(&json).unwrap();
+
+        assert!(other == verif);
+        assert_eq!(other.mask.count_ones(), 25);
+        assert_eq!(other.mask.to_string(), verif.mask.to_string());
+        assert!(other.mask == Verifier::new(&gc, other.rule.clone()).unwrap().mask);
+    }
+
+    #[test]
+    fn test_info_bits() {
+        let gc = test_configuration(3, 5);
+        let info_bits = |rule| Verifier::new(&gc, rule).unwrap().info_bits(&gc);
+
+        let selective = info_bits(Rule::ColumnEquals(Column::from(0), 2));
+        let weak = info_bits(Rule::MatchesOp(
+            Operator::Pair,
+            HashSet::from([Column::from(0)]).into(),
+        ));
+        assert!((selective - 5f64.log2()).abs() < 1e-9);
+        assert!(selective > weak && weak > 0.0);
+
+        let unsatisfiable = info_bits(Rule::ColumnEquals(Column::from(0), 7));
+        assert!(unsatisfiable.is_finite());
+        assert_eq!(unsatisfiable, 0.0);
+    }
+
+    #[test]
+    fn test_access() {
+        let gc = test_configuration(2, 3);
+        let verificators: Verificators = vec![
+            Verifier::new(&gc, Rule::ColumnEquals(Column::from(0), 1)).unwrap(),
+            Verifier::new(&gc, Rule::ColumnEquals(Column::from(1), 1)).unwrap(),
+        ]
+        .into();
+        assert_eq!(verificators.len(), 2);
+        assert!(!verificators.is_empty());
+        assert!(verificators.get(1).unwrap().rule == Rule::ColumnEquals(Column::from(1), 1));
+        assert!(verificators.get(2).is_none());
+
+        let empty: Verificators = Vec::new().into();
+        assert!(empty.is_empty());
+        assert!(empty.get(0).is_none());
+    }
+}
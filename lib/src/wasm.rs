@@ -0,0 +1,135 @@
+use crate::setup::{generate_game_with_rng, Game, GenerationOptions, PublicGame, MAX_BIDS};
+use rand::{rngs::StdRng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+fn generate(
+    base: u8,
+    column_count: u8,
+    min_difficulty: u8,
+    seed: Option<u64>,
+) -> Result<Game, String> {
+    let options = GenerationOptions {
+        base,
+        column_count,
+        min_difficulty,
+        seed,
+        ..Default::default()
+    };
+    generate_game_with_rng(options, &mut StdRng::from_entropy()).map_err(|e| e.to_string())
+}
+
+/// Generates a game as JSON, the seed coming from the browser's crypto API when not given.
+///
+/// The JSON holds the secret code and the verifiers, so this is only meant for offline tools
+/// and self-play. Pages letting someone play should use `HiddenGame` instead.
+#[wasm_bindgen(js_name = generateGame)]
+pub fn generate_game_json(
+    base: u8,
+    column_count: u8,
+    min_difficulty: u8,
+    seed: Option<u64>,
+) -> Result<String, String> {
+    let game = generate(base, column_count, min_difficulty, seed)?;
+    serde_json::to_string(&game).map_err(|e| e.to_string())
+}
+
+/// Opaque handle on a game, only handing its public part to JS and answering tests and bids.
+#[wasm_bindgen]
+pub struct HiddenGame {
+    game: Game,
+    bids: u32,
+}
+
+#[wasm_bindgen]
+impl HiddenGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base: u8,
+        column_count: u8,
+        min_difficulty: u8,
+        seed: Option<u64>,
+    ) -> Result<HiddenGame, String> {
+        let game = generate(base, column_count, min_difficulty, seed)?;
+        Ok(HiddenGame { game, bids: 0 })
+    }
+
+    /// The configuration and criteria as JSON, without the code or the verifiers.
+    #[wasm_bindgen(js_name = publicGame)]
+    pub fn public_game(&self) -> Result<String, String> {
+        serde_json::to_string(&PublicGame::from(&self.game)).map_err(|e| e.to_string())
+    }
+
+    /// Whether `code` passes the criteria `crit_id`.
+    pub fn test(&self, code: &str, crit_id: u8) -> Result<bool, String> {
+        let code = self
+            .game
+            .configuration
+            .parse_code(code)
+            .map_err(|e| e.to_string())?;
+        let crit = self
+            .game
+            .criterion(crit_id)
+            .ok_or_else(|| format!("Unknown criteria {crit_id}"))?;
+        crit.verif.rule.evaluate(code).map_err(|e| e.to_string())
+    }
+
+    /// Whether `code` is the secret code, as long as bids are left. Malformed codes cost none.
+    pub fn bid(&mut self, code: &str) -> Result<bool, String> {
+        let code = self
+            .game
+            .configuration
+            .parse_code(code)
+            .map_err(|e| e.to_string())?;
+        if self.bids >= MAX_BIDS {
+            return Err("No bids left".to_string());
+        }
+        self.bids += 1;
+        Ok(code == self.game.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_game_json, HiddenGame};
+    use crate::setup::{generate_game_seeded, Game, MAX_BIDS};
+
+    #[test]
+    fn test_generate_game_json() {
+        let export: fn(u8, u8, u8, Option<u64>) -> Result<String, String> = generate_game_json;
+
+        let json = export(5, 3, 20, Some(8)).unwrap();
+        let game: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(game.code, generate_game_seeded(5, 3, 20, 8).unwrap().code);
+
+        let game: Game = serde_json::from_str(&export(5, 3, 20, None).unwrap()).unwrap();
+        assert_eq!(game.consistent_codes(), vec![game.code.clone()]);
+
+        assert!(export(1, 3, 20, None).is_err());
+    }
+
+    #[test]
+    fn test_hidden_game() {
+        let game = generate_game_seeded(5, 3, 20, 8).unwrap();
+        let mut hidden = HiddenGame::new(5, 3, 20, Some(8)).unwrap();
+
+        let json = hidden.public_game().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value.get("code").is_none());
+        assert!(!json.contains("verif"));
+        assert!(!json.contains("mask"));
+
+        let code = game.code.to_string();
+        for crit in game.criterias.iter() {
+            assert!(hidden.test(&code, crit.id).unwrap());
+        }
+        assert!(hidden.test(&code, game.criteria_count()).is_err());
+        assert!(hidden.test("9", 0).is_err());
+        assert!(hidden.bid("9").is_err());
+        assert!(hidden.bid(&code).unwrap());
+        let wrong = if code == "000" { "111" } else { "000" };
+        for _ in 1..MAX_BIDS {
+            assert!(!hidden.bid(wrong).unwrap());
+        }
+        assert_eq!(hidden.bid(&code), Err("No bids left".to_string()));
+    }
+}
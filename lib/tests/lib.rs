@@ -1,6 +1,262 @@
-use enigmind_lib::setup::generate_game;
+#![cfg(feature = "generation")]
+
+use enigmind_lib::{
+    code::Code,
+    error::EnigmindError,
+    rules::Rules,
+    setup::{generate_game_seeded, generate_game_simple, Game, SCHEMA_VERSION},
+    solver::TestResult,
+};
 
 #[test]
 fn test_print() {
-    let _game = generate_game(5, 3, 20).unwrap();
+    let _game = generate_game_simple(5, 3, 20).unwrap();
+}
+
+#[test]
+fn test_seeded_generation_is_reproducible() {
+    let game = generate_game_seeded(5, 3, 20, 42).unwrap();
+    let other = generate_game_seeded(5, 3, 20, 42).unwrap();
+
+    assert_eq!(game.seed, 42);
+    assert_eq!(game.code, other.code);
+    assert_eq!(
+        serde_json::to_string(&game).unwrap(),
+        serde_json::to_string(&other).unwrap()
+    );
+}
+
+#[test]
+fn test_generated_games_have_a_unique_solution() {
+    for seed in 0..5 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+        let codes = game.consistent_codes();
+
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0], game.code);
+    }
+}
+
+#[test]
+fn test_base_9_games() {
+    for seed in 0..3 {
+        let game = generate_game_seeded(9, 2, 0, seed).unwrap();
+        let codes = game.consistent_codes();
+
+        assert_eq!(codes, vec![game.code.clone()]);
+        assert!(game.code.is_well_formed(&game.configuration));
+
+        let printed = game.code.to_string();
+        assert_eq!(printed.len(), 2);
+        assert_eq!(game.configuration.parse_code(&printed).unwrap(), game.code);
+    }
+}
+
+#[test]
+fn test_criteria_ids_are_stable() {
+    let labels = |seed| {
+        generate_game_seeded(5, 3, 20, seed)
+            .unwrap()
+            .criterias
+            .iter()
+            .map(|crit| (crit.id, crit.description.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    let first = labels(7);
+    assert_eq!(first, labels(7));
+    for (index, (id, _)) in first.iter().enumerate() {
+        assert_eq!(*id as usize, index);
+    }
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let game = generate_game_seeded(5, 3, 20, 3).unwrap();
+
+    let json = serde_json::to_string(&game).unwrap();
+    let other: Game = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(other.configuration, game.configuration);
+    assert_eq!(other.configuration.schema_version, SCHEMA_VERSION);
+    assert_eq!(other.code, game.code);
+    assert_eq!(other.seed, game.seed);
+    assert_eq!(other.criterias.len(), game.criterias.len());
+    for (crit, other_crit) in game.criterias.iter().zip(other.criterias.iter()) {
+        assert_eq!(other_crit.id, crit.id);
+        assert_eq!(other_crit.description, crit.description);
+        assert!(other_crit.verif == crit.verif);
+        assert!((other_crit.info_bits - crit.info_bits).abs() < 1e-9);
+        assert!(crit.info_bits.is_finite() && crit.info_bits > 0.0);
+        assert_eq!(
+            other_crit.verif.mask.count_ones(),
+            crit.verif.mask.count_ones()
+        );
+        assert_eq!(
+            other_crit.verif.mask.to_string(),
+            crit.verif.mask.to_string()
+        );
+    }
+}
+
+#[test]
+fn test_rules_parse_back() {
+    for seed in 0..3 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+        let gc = &game.configuration;
+
+        for crit in game.criterias.iter() {
+            let text = crit.rules.to_string();
+            let parsed = Rules::from_reader(text.as_bytes()).unwrap();
+
+            assert_eq!(parsed.to_string(), text);
+            for (rule, parsed) in crit.rules.iter().zip(parsed.iter()) {
+                assert_eq!(
+                    parsed.get_mask(gc).unwrap().to_string(),
+                    rule.get_mask(gc).unwrap().to_string()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_optimal_solution_path() {
+    for seed in 0..3 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+        let path = game.optimal_solution_path();
+
+        let mut remaining = game.configuration.solution_count() as usize;
+        for step in 1..=path.len() {
+            let candidates = game.remaining_candidates(&path[..step]).unwrap();
+            assert!(candidates.len() < remaining);
+            remaining = candidates.len();
+        }
+        assert_eq!(
+            game.remaining_candidates(&path).unwrap(),
+            vec![game.code.clone()]
+        );
+
+        let code = game.code.clone();
+        assert!(game
+            .remaining_candidates(&[(code.clone(), game.criteria_count())])
+            .is_err());
+        assert!(game
+            .remaining_candidates(&[(Code::new(vec![0; 4]), 0)])
+            .is_err());
+    }
+}
+
+#[test]
+fn test_recommend_test() {
+    for seed in 0..3 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+
+        let mut known: Vec<TestResult> = Vec::new();
+        while let Some((code, criterion)) = game.recommend_test(&known).unwrap() {
+            // Either outcome must still be possible, or the test teaches nothing
+            for passed in [true, false] {
+                let mut guessed = known.clone();
+                guessed.push(TestResult {
+                    code: code.clone(),
+                    criterion,
+                    passed,
+                });
+                assert!(!game.candidates_after(&guessed).unwrap().is_empty());
+            }
+
+            let crit = game.criterion(criterion).unwrap();
+            let passed = crit.verif.rule.evaluate(code.clone()).unwrap();
+            known.push(TestResult {
+                code,
+                criterion,
+                passed,
+            });
+            assert!(known.len() <= 20);
+        }
+
+        assert_eq!(
+            game.candidates_after(&known).unwrap(),
+            vec![game.code.clone()]
+        );
+    }
+}
+
+#[test]
+fn test_malformed_test_results() {
+    let game = generate_game_seeded(5, 3, 20, 1).unwrap();
+    let result = |digits: Vec<u8>, criterion| TestResult {
+        code: Code::new(digits),
+        criterion,
+        passed: true,
+    };
+
+    for malformed in [
+        result(vec![0, 1], 0),
+        result(vec![0, 1, 2, 3], 0),
+        result(vec![0, 5, 2], 0),
+        result(vec![0, 1, 2], game.criteria_count()),
+    ] {
+        assert!(matches!(
+            game.candidates_after(std::slice::from_ref(&malformed)),
+            Err(EnigmindError::InvalidTestResult(_))
+        ));
+        assert!(game.recommend_test(&[malformed]).is_err());
+    }
+    assert!(game.candidates_after(&[result(vec![0, 1, 2], 0)]).is_ok());
+}
+
+#[test]
+fn test_sheet_hides_the_solution() {
+    let game = generate_game_seeded(5, 3, 20, 4).unwrap();
+    let sheet = game.to_sheet();
+
+    assert!(!sheet.contains(&game.code.to_string()));
+    for crit in game.criterias.iter() {
+        assert!(sheet.contains(&crit.description));
+    }
+}
+
+#[test]
+fn test_candidate_masks() {
+    for seed in 0..5 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+
+        for crit in game.criterias.iter() {
+            let masks = crit.candidate_masks(&game.configuration).unwrap();
+
+            assert_eq!(masks.len(), crit.rules.len());
+            assert_eq!(
+                masks
+                    .iter()
+                    .filter(|(_, mask)| *mask == crit.verif.mask)
+                    .count(),
+                1
+            );
+        }
+    }
+}
+
+#[test]
+fn test_accepted_codes() {
+    for seed in 0..3 {
+        let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+        let gc = &game.configuration;
+
+        for crit in game.criterias.iter() {
+            let accepted = crit.accepted_codes(gc);
+            let rejected = crit.rejected_codes(gc);
+
+            assert_eq!(accepted.len(), crit.verif.mask.count_ones());
+            assert!(accepted.contains(&game.code));
+            assert!(!rejected.contains(&game.code));
+            for code in accepted.iter() {
+                assert!(crit.verif.rule.evaluate(code.clone()).unwrap());
+            }
+
+            let mut all: Vec<Code> = accepted.into_iter().chain(rejected).collect();
+            all.sort_by_key(|code| code.get_shift(gc));
+            assert_eq!(all, gc.codes().collect::<Vec<_>>());
+        }
+    }
 }
@@ -0,0 +1,37 @@
+#![cfg(feature = "generation")]
+
+use enigmind_lib::setup::generate_game_seeded;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+struct CaptureLogger(Mutex<Vec<(Level, String)>>);
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+#[test]
+fn test_rule_selection_is_traced() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    generate_game_seeded(5, 3, 20, 42).unwrap();
+
+    let records = LOGGER.0.lock().unwrap();
+    assert!(records
+        .iter()
+        .any(|(level, msg)| *level == Level::Trace && msg.contains("chosen.")));
+}
@@ -0,0 +1,88 @@
+#![cfg(all(feature = "generation", feature = "schema"))]
+
+use enigmind_lib::setup::{generate_game_seeded, public_game_schema, PublicGame};
+use jsonschema::JSONSchema;
+
+fn public_game(seed: u64) -> serde_json::Value {
+    let game = generate_game_seeded(5, 3, 20, seed).unwrap();
+    serde_json::to_value(PublicGame::from(&game)).unwrap()
+}
+
+#[test]
+fn test_schema_validates_games() {
+    let schema = serde_json::to_value(public_game_schema()).unwrap();
+    let compiled = JSONSchema::compile(&schema).unwrap();
+
+    for seed in 0..5 {
+        let game = public_game(seed);
+        let errors: Vec<String> = match compiled.validate(&game) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.map(|e| e.to_string()).collect(),
+        };
+        assert!(errors.is_empty(), "seed {seed}: {errors:?}");
+    }
+
+    let mut game = public_game(0);
+    game["criterias"][0]["rules"][0]["rule"] = "Unknown".into();
+    assert!(!compiled.is_valid(&game));
+}
+
+/// Names of every property described by the schema, nested ones included.
+fn property_names(schema: &serde_json::Value, names: &mut Vec<String>) {
+    match schema {
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::Object(properties)) = object.get("properties") {
+                names.extend(properties.keys().cloned());
+            }
+            object
+                .values()
+                .for_each(|value| property_names(value, names));
+        }
+        serde_json::Value::Array(values) => {
+            values.iter().for_each(|value| property_names(value, names));
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn test_schema_hides_verifiers() {
+    let schema = serde_json::to_value(public_game_schema()).unwrap();
+
+    let mut names = Vec::new();
+    property_names(&schema, &mut names);
+    for hidden in ["code", "verif", "mask", "info_bits"] {
+        assert!(
+            !names.iter().any(|name| name == hidden),
+            "schema has {hidden}"
+        );
+    }
+    assert!(schema["definitions"].get("Verifier").is_none());
+}
+
+#[test]
+fn test_schema_lists_operators() {
+    let schema = serde_json::to_value(public_game_schema()).unwrap();
+    let operator = schema["definitions"]["Operator"].to_string();
+
+    for name in [
+        "Pair",
+        "Impair",
+        "Lowest",
+        "Highest",
+        "SumBelow",
+        "SumEquals",
+        "SumAbove",
+        "SumMultipleOf",
+        "SumBetween",
+        "ProductBelow",
+        "ProductEquals",
+        "ProductAbove",
+        "SameParity",
+        "AlternatingParity",
+        "MaxEquals",
+        "MinEquals",
+    ] {
+        assert!(operator.contains(&format!("\"{name}\"")), "missing {name}");
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+use enigmind_lib::setup::Game;
+use serde::{Deserialize, Serialize};
+
+/// Generation parameters of a seeded game: base, column count, difficulty and seed.
+pub type CacheKey = (u8, u8, u8, u64);
+
+/// Recently generated seeded games, the least recently used being evicted first.
+pub struct GameCache {
+    capacity: usize,
+    // Most recently used last
+    games: VecDeque<(CacheKey, Game)>,
+    hits: u64,
+    misses: u64,
+    generations: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CacheStats {
+    pub size: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub generations: u64,
+}
+
+impl GameCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            games: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+            generations: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &CacheKey) -> Option<Game> {
+        let Some(index) = self.games.iter().position(|(k, _)| k == key) else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.hits += 1;
+        let entry = self.games.remove(index)?;
+        let game = entry.1.clone();
+        self.games.push_back(entry);
+        Some(game)
+    }
+
+    pub fn insert(&mut self, key: CacheKey, game: Game) {
+        self.games.retain(|(k, _)| *k != key);
+        if self.capacity == 0 {
+            return;
+        }
+        if self.games.len() == self.capacity {
+            self.games.pop_front();
+        }
+        self.games.push_back((key, game));
+    }
+
+    /// Counts a game generated from scratch, cached or not.
+    pub fn count_generation(&mut self) {
+        self.generations += 1;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            size: self.games.len(),
+            hits: self.hits,
+            misses: self.misses,
+            generations: self.generations,
+        }
+    }
+}
+
+impl Default for GameCache {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::GameCache;
+    use enigmind_lib::setup::generate_game_seeded;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = GameCache::new(2);
+        for seed in 0..2 {
+            cache.insert(
+                (5, 3, 0, seed),
+                generate_game_seeded(5, 3, 0, seed).unwrap(),
+            );
+        }
+
+        assert_eq!(cache.get(&(5, 3, 0, 0)).unwrap().seed, 0);
+        cache.insert((5, 3, 0, 2), generate_game_seeded(5, 3, 0, 2).unwrap());
+
+        assert!(cache.get(&(5, 3, 0, 1)).is_none());
+        assert!(cache.get(&(5, 3, 0, 0)).is_some());
+        assert!(cache.get(&(5, 3, 0, 2)).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.size, 2);
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+    }
+}
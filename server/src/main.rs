@@ -1,29 +1,246 @@
 #![deny(clippy::all, clippy::unwrap_used)]
 
-use std::{collections::HashMap, process::exit};
+mod cache;
+mod metrics;
+mod rate_limit;
+mod sessions;
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    io::IsTerminal,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+    time::Instant,
+};
 
 use axum::{
-    extract::Query,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query,
+    },
+    http::{header, HeaderName, StatusCode},
+    middleware,
     response::{IntoResponse, Response},
     routing::get,
-    Json, Router,
+    Extension, Json, Router,
 };
-use enigmind_lib::setup::generate_game;
+use enigmind_lib::{
+    code::Code,
+    criteria::PublicCriteria,
+    error::EnigmindError,
+    rule::Rule,
+    setup::{
+        generate_game, Game, GameConfiguration, GenerationOptions, PublicGame, MAX_BASE, MAX_BIDS,
+    },
+};
+use log::debug;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use cache::{CacheStats, GameCache};
+use metrics::SharedMetrics;
+use rate_limit::{rate_limit, Limiter};
+use sessions::{SessionStore, Sessions};
+
+type Cache = Arc<Mutex<GameCache>>;
+
+/// Error body of every endpoint, `code` being stable for clients to match on.
+#[derive(Serialize)]
+struct ApiError {
+    #[serde(skip)]
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<EnigmindError> for ApiError {
+    fn from(e: EnigmindError) -> Self {
+        let (status, code) = match e {
+            EnigmindError::BitmaskError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "bitmask_error"),
+            EnigmindError::ColumnIndexOutOfBounds => {
+                (StatusCode::BAD_REQUEST, "column_index_out_of_bounds")
+            }
+            EnigmindError::EmptyCode => (StatusCode::BAD_REQUEST, "empty_code"),
+            EnigmindError::InvalidDigit(_) => (StatusCode::BAD_REQUEST, "invalid_digit"),
+            EnigmindError::DigitOutOfBounds(_) => (StatusCode::BAD_REQUEST, "digit_out_of_bounds"),
+            EnigmindError::WrongCodeLength { .. } => (StatusCode::BAD_REQUEST, "wrong_code_length"),
+            EnigmindError::ConfigurationTooLarge => {
+                (StatusCode::BAD_REQUEST, "configuration_too_large")
+            }
+            // Such as 9 digits over 5 columns, too many codes to generate a game from
+            EnigmindError::InvalidConfiguration { .. } => {
+                (StatusCode::BAD_REQUEST, "invalid_configuration")
+            }
+            EnigmindError::InvalidRange { .. } => (StatusCode::BAD_REQUEST, "invalid_range"),
+            // The parameters are valid, but no game could be generated from them
+            EnigmindError::NoRulesAvailable => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "no_rules_available")
+            }
+            EnigmindError::NoUniqueSolution => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "no_unique_solution")
+            }
+            EnigmindError::CannotSatisfyConstraints => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "cannot_satisfy_constraints",
+            ),
+            EnigmindError::GenerationTimeout => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "generation_timeout")
+            }
+            EnigmindError::UnsupportedSchemaVersion(_) => {
+                (StatusCode::BAD_REQUEST, "unsupported_schema_version")
+            }
+            EnigmindError::ParseError(_) => (StatusCode::BAD_REQUEST, "parse_error"),
+            EnigmindError::InvalidTestResult(_) => (StatusCode::BAD_REQUEST, "invalid_test_result"),
+        };
+        Self::new(status, code, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+/// A game being played, with the test results already computed for it.
+struct Session {
+    game: Game,
+    // Keyed by code shift and criteria index
+    results: HashMap<(u32, u8), bool>,
+    lookups: u32,
+    hits: u32,
+    attempts: u32,
+    bids: u32,
+    /// Verifiers the player may still reveal
+    reveal_budget: u8,
+    revealed: HashSet<u8>,
+}
+
+impl Session {
+    fn new(game: Game) -> Self {
+        Self {
+            game,
+            results: HashMap::new(),
+            lookups: 0,
+            hits: 0,
+            attempts: 0,
+            bids: 0,
+            reveal_budget: 0,
+            revealed: HashSet::new(),
+        }
+    }
+
+    fn test(&mut self, code: &Code, crit_index: u8) -> Result<bool, String> {
+        let key = (code.get_shift(&self.game.configuration), crit_index);
+        self.lookups += 1;
+        if let Some(result) = self.results.get(&key) {
+            self.hits += 1;
+            return Ok(*result);
+        }
+
+        let Some(crit) = self.game.criterion(crit_index) else {
+            return Err(format!("Unknown criteria {crit_index}"));
+        };
+        let result = crit
+            .verif
+            .rule
+            .evaluate(code.clone())
+            .map_err(|e| e.to_string())?;
+        self.results.insert(key, result);
+        Ok(result)
+    }
+
+    /// Checks every criteria of a test exists, so that a test failing on one costs no attempt.
+    fn check_criterias(&self, crits: &[u8]) -> Result<(), String> {
+        match crits
+            .iter()
+            .find(|crit| self.game.criterion(**crit).is_none())
+        {
+            Some(crit) => Err(format!("Unknown criteria {crit}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a test against the allowed attempts, returning whether it went over them.
+    fn count_attempt(&mut self) -> bool {
+        self.attempts += 1;
+        self.game.configuration.is_lost(self.attempts)
+    }
+
+    fn cache_size(&self) -> usize {
+        self.results.len()
+    }
+
+    fn hit_rate(&self) -> f64 {
+        match self.lookups {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // build our application with a single route
-    let app = Router::new()
-        .route("/generate", get(generate))
-        .route("/ping", get(ping));
+    env_logger::init();
+
+    let listener = TcpListener::bind(bind_address())?;
+    serve(listener, shutdown_signal()).await
+}
+
+const DEFAULT_BIND: &str = "0.0.0.0:3000";
 
-    // run it with hyper on localhost:3000
+fn bind_address() -> String {
+    std::env::var("ENIGMIND_BIND").unwrap_or_else(|_| DEFAULT_BIND.to_string())
+}
 
-    axum::Server::bind(&"0.0.0.0:3000".parse()?)
-        .serve(app.into_make_service())
+/// Serves the app until `shutdown` resolves, letting in-flight requests complete.
+async fn serve(listener: TcpListener, shutdown: impl Future<Output = ()>) -> anyhow::Result<()> {
+    axum::Server::from_tcp(listener)?
+        .serve(app().into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown)
         .await?;
 
-    exit(0);
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        debug!("Cannot listen for Ctrl-C: {e}");
+        std::future::pending::<()>().await;
+    }
+    debug!("Shutting down");
+}
+
+fn app() -> Router {
+    Router::new()
+        // Routes generating games, limited as they are the heaviest ones
+        .route("/generate", get(generate))
+        .route("/play", get(play))
+        .route_layer(middleware::from_fn(rate_limit))
+        .route("/ping", get(ping))
+        .route("/test", get(test_code))
+        .route("/bid", get(bid))
+        .route("/reveal", get(reveal))
+        .route("/forfeit", get(forfeit))
+        .route("/stats", get(stats))
+        .route("/metrics", get(metrics))
+        .route("/schema", get(schema))
+        .layer(Extension(Sessions::default()))
+        .layer(Extension(Cache::default()))
+        .layer(Extension(SharedMetrics::default()))
+        .layer(Extension(Limiter::default()))
 }
 
 async fn ping() -> Response {
@@ -38,13 +255,1116 @@ fn extract_u8_param_or(params: &HashMap<String, String>, name: &str, default: u8
         .unwrap_or(default)
 }
 
-async fn generate(Query(params): Query<HashMap<String, String>>) -> Response {
-    let base = extract_u8_param_or(&params, "base", 5);
-    let column_count = extract_u8_param_or(&params, "column_count", 3);
-    let difficulty_pct = extract_u8_param_or(&params, "difficulty_pct", 10);
+/// Generates the requested game, or takes it from the cache when seeded.
+async fn requested_game(
+    params: &HashMap<String, String>,
+    cache: &Cache,
+    metrics: &SharedMetrics,
+) -> Result<Game, ApiError> {
+    let base = extract_u8_param_or(params, "base", 5);
+    let column_count = extract_u8_param_or(params, "column_count", 3);
+    let difficulty = extract_u8_param_or(params, "difficulty", 0).min(100);
+    let seed = params.get("seed").and_then(|s| s.parse::<u64>().ok());
+    let max_attempts = params
+        .get("max_attempts")
+        .and_then(|s| s.parse::<u32>().ok());
+
+    if !(2..=MAX_BASE).contains(&base) || !(1..=5).contains(&column_count) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_parameters",
+            format!("base must be between 2 and {MAX_BASE}, column_count between 1 and 5"),
+        ));
+    }
+
+    // The attempt limit doesn't change the generated game, so it isn't part of the key
+    let key = seed.map(|seed| (base, column_count, difficulty, seed));
+    let cached = match &key {
+        Some(key) => cache.lock().await.get(key),
+        None => None,
+    };
+
+    let mut game = match cached {
+        Some(game) => game,
+        None => {
+            let start = Instant::now();
+            let options = GenerationOptions {
+                base,
+                column_count,
+                min_difficulty: difficulty,
+                seed,
+                // env_logger writes to stderr
+                colored_logs: std::io::stderr().is_terminal(),
+                ..Default::default()
+            };
+            // Generation keeps a thread busy, which the other requests must not wait on
+            let generated = match tokio::task::spawn_blocking(move || generate_game(options)).await
+            {
+                Ok(generated) => generated.map_err(ApiError::from),
+                Err(e) => Err(ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "generation_failed",
+                    e.to_string(),
+                )),
+            };
+
+            let mut recorded = metrics.lock().await;
+            recorded.record_generation(start.elapsed());
+            let game = match generated {
+                Ok(game) => game,
+                Err(error) => {
+                    recorded.record_failure(error.code);
+                    return Err(error);
+                }
+            };
+            recorded.record_game(base, column_count);
+            drop(recorded);
+
+            let mut cache = cache.lock().await;
+            cache.count_generation();
+            if let Some(key) = key {
+                cache.insert(key, game.clone());
+            }
+            game
+        }
+    };
+    game.configuration.max_attempts = max_attempts;
+
+    Ok(game)
+}
+
+/// Error for a session the store doesn't hold, telling apart those it dropped.
+fn missing_session(sessions: &SessionStore, id: &str) -> ApiError {
+    match sessions.is_expired(id) {
+        true => ApiError::new(
+            StatusCode::GONE,
+            "session_expired",
+            "Session expired, start a new game",
+        ),
+        false => ApiError::new(StatusCode::NOT_FOUND, "unknown_session", "Unknown session"),
+    }
+}
+
+fn new_session() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// Session of a game generated with `format=bin`, the body holding only the game.
+const SESSION_HEADER: &str = "x-enigmind-session";
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct GeneratedGame {
+    session: String,
+    game: PublicGame,
+}
+
+async fn generate(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+    Extension(cache): Extension<Cache>,
+    Extension(metrics): Extension<SharedMetrics>,
+) -> Response {
+    match requested_game(&params, &cache, &metrics).await {
+        Ok(game) => {
+            let generated = GeneratedGame {
+                session: new_session(),
+                game: PublicGame::from(&game),
+            };
+            let mut session = Session::new(game);
+            session.reveal_budget = extract_u8_param_or(&params, "reveal_budget", 0);
+            sessions
+                .lock()
+                .await
+                .insert(generated.session.clone(), session, Instant::now());
+            if params.get("format").map(String::as_str) == Some("bin") {
+                return binary_game(generated);
+            }
+            Json(generated).into_response()
+        }
+        Err(error) => error.into_response(),
+    }
+}
+
+/// The public game as MessagePack, its session being sent as a header.
+fn binary_game(generated: GeneratedGame) -> Response {
+    match generated.game.to_bytes() {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "application/x-msgpack".to_string()),
+                (HeaderName::from_static(SESSION_HEADER), generated.session),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(error) => ApiError::from(error).into_response(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestResults {
+    results: Vec<(u8, bool)>,
+    /// The code, revealed once the tests went over the allowed attempts, ending the session
+    lost: Option<Code>,
+}
+
+/// Tests a code against criteria of a session's game, given as `crits=0,2`, so that the
+/// verifiers never leave the server.
+async fn test_code(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+) -> Response {
+    let id = params.get("session").cloned().unwrap_or_default();
+    let mut sessions = sessions.lock().await;
+    let Some(session) = sessions.get_mut(&id, Instant::now()) else {
+        return missing_session(&sessions, &id).into_response();
+    };
+
+    let code = params.get("code").cloned().unwrap_or_default();
+    let code = match session.game.configuration.parse_code(&code) {
+        Ok(code) => code,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let crits: Option<Vec<u8>> = params
+        .get("crits")
+        .map(|crits| crits.split(',').map(|crit| crit.parse().ok()).collect())
+        .unwrap_or_default();
+    let Some(crits) = crits else {
+        return ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_criteria",
+            "crits must be a list of criteria ids",
+        )
+        .into_response();
+    };
+    if let Err(e) = session.check_criterias(&crits) {
+        return ApiError::new(StatusCode::BAD_REQUEST, "invalid_criteria", e).into_response();
+    }
+
+    let mut results = Vec::new();
+    for crit_index in crits {
+        if session.count_attempt() {
+            let lost = Some(session.game.code.clone());
+            sessions.remove(&id);
+            return Json(TestResults { results, lost }).into_response();
+        }
+        match session.test(&code, crit_index) {
+            Ok(result) => results.push((crit_index, result)),
+            Err(e) => {
+                return ApiError::new(StatusCode::BAD_REQUEST, "invalid_criteria", e)
+                    .into_response()
+            }
+        }
+    }
+    Json(TestResults {
+        results,
+        lost: None,
+    })
+    .into_response()
+}
+
+#[derive(Serialize, Deserialize)]
+struct BidResult {
+    win: bool,
+    /// The session ends once no bids are left
+    bids_left: u32,
+    /// The code, revealed once the last bid was wrong
+    code: Option<Code>,
+}
+
+async fn bid(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+) -> Response {
+    let id = params.get("session").cloned().unwrap_or_default();
+    let mut sessions = sessions.lock().await;
+    let Some(session) = sessions.get_mut(&id, Instant::now()) else {
+        return missing_session(&sessions, &id).into_response();
+    };
+
+    let code = params.get("code").cloned().unwrap_or_default();
+    let code = match session.game.configuration.parse_code(&code) {
+        Ok(code) => code,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    session.bids += 1;
+    let win = code == session.game.code;
+    let bids_left = MAX_BIDS.saturating_sub(session.bids);
+    let code = (!win && bids_left == 0).then(|| session.game.code.clone());
+    if win || bids_left == 0 {
+        sessions.remove(&id);
+    }
+    Json(BidResult {
+        win,
+        bids_left,
+        code,
+    })
+    .into_response()
+}
+
+#[derive(Serialize, Deserialize)]
+struct RevealResult {
+    rule: Rule,
+    reveals_left: u8,
+}
+
+/// Reveals the verifier of a criteria, given as `crit=1`, spending one of the reveals the
+/// session was generated with. Revealing a criteria again costs nothing.
+async fn reveal(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+) -> Response {
+    let id = params.get("session").cloned().unwrap_or_default();
+    let mut sessions = sessions.lock().await;
+    let Some(session) = sessions.get_mut(&id, Instant::now()) else {
+        return missing_session(&sessions, &id).into_response();
+    };
+
+    let Some(crit) = params
+        .get("crit")
+        .and_then(|crit| crit.parse::<u8>().ok())
+        .and_then(|crit| session.game.criterion(crit))
+    else {
+        return ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_criteria",
+            "crit must be a criteria id",
+        )
+        .into_response();
+    };
+
+    if !session.revealed.contains(&crit.id) {
+        if session.reveal_budget == 0 {
+            return ApiError::new(StatusCode::FORBIDDEN, "no_reveals_left", "No reveals left")
+                .into_response();
+        }
+        session.reveal_budget -= 1;
+        session.revealed.insert(crit.id);
+    }
+    Json(RevealResult {
+        rule: crit.verif.rule.clone(),
+        reveals_left: session.reveal_budget,
+    })
+    .into_response()
+}
+
+/// Ends a game the player gave up on, or ran out of attempts for, revealing its code.
+async fn forfeit(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+) -> Response {
+    let session = params.get("session").cloned().unwrap_or_default();
+    let mut sessions = sessions.lock().await;
+    match sessions.remove(&session) {
+        Some(session) => Json(session.game.code).into_response(),
+        None => missing_session(&sessions, &session).into_response(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stats {
+    sessions: usize,
+    cache: CacheStats,
+    rate_limited: u64,
+}
+
+async fn stats(
+    Extension(sessions): Extension<Sessions>,
+    Extension(cache): Extension<Cache>,
+    Extension(limiter): Extension<Limiter>,
+) -> Response {
+    Json(Stats {
+        sessions: sessions.lock().await.len(),
+        cache: cache.lock().await.stats(),
+        rate_limited: limiter.lock().await.rejected(),
+    })
+    .into_response()
+}
+
+/// Generation counters in the Prometheus text format.
+async fn metrics(Extension(metrics): Extension<SharedMetrics>) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.lock().await.render(),
+    )
+        .into_response()
+}
+
+/// JSON Schema of the `/generate` response.
+async fn schema() -> Response {
+    Json(schema_for!(GeneratedGame)).into_response()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ClientMessage {
+    Test { code: Code, crits: Vec<u8> },
+    Bid { code: Code },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ServerMessage {
+    Start {
+        session: String,
+        configuration: GameConfiguration,
+        criterias: Vec<PublicCriteria>,
+    },
+    Test {
+        results: Vec<(u8, bool)>,
+    },
+    Bid {
+        win: bool,
+    },
+    /// Sent instead of the test result going over the allowed attempts
+    Lost {
+        code: Code,
+    },
+    Error(String),
+}
+
+async fn play(
+    ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
+    Extension(sessions): Extension<Sessions>,
+    Extension(cache): Extension<Cache>,
+    Extension(metrics): Extension<SharedMetrics>,
+) -> Response {
+    match requested_game(&params, &cache, &metrics).await {
+        Ok(game) => ws.on_upgrade(move |socket| play_session(socket, sessions, game)),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn play_session(mut socket: WebSocket, sessions: Sessions, game: Game) {
+    let session = new_session();
+
+    let start = ServerMessage::Start {
+        session: session.clone(),
+        configuration: game.configuration.clone(),
+        criterias: game.criterias.iter().map(PublicCriteria::from).collect(),
+    };
+    sessions
+        .lock()
+        .await
+        .insert(session.clone(), Session::new(game), Instant::now());
+
+    if send(&mut socket, &start).await {
+        while let Some(Ok(message)) = socket.recv().await {
+            let Message::Text(text) = message else {
+                continue;
+            };
+
+            let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(message) => {
+                    let mut sessions = sessions.lock().await;
+                    match sessions.get_mut(&session, Instant::now()) {
+                        Some(session) => answer(session, message),
+                        None => ServerMessage::Error(missing_session(&sessions, &session).message),
+                    }
+                }
+                Err(e) => ServerMessage::Error(e.to_string()),
+            };
+
+            let finished = matches!(
+                reply,
+                ServerMessage::Bid { .. } | ServerMessage::Lost { .. }
+            );
+            if !send(&mut socket, &reply).await || finished {
+                break;
+            }
+        }
+    }
+
+    if let Some(ended) = sessions.lock().await.remove(&session) {
+        debug!(
+            "Session {session} ended with {} cached results, {:.0}% hit rate",
+            ended.cache_size(),
+            ended.hit_rate() * 100.0
+        );
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn answer(session: &mut Session, message: ClientMessage) -> ServerMessage {
+    match message {
+        ClientMessage::Test { code, crits } => {
+            if !session.game.is_solution_compatible(&code) {
+                return ServerMessage::Error("Invalid code".to_string());
+            }
+            if let Err(e) = session.check_criterias(&crits) {
+                return ServerMessage::Error(e);
+            }
+
+            let mut results = vec![];
+            for crit_index in crits {
+                if session.count_attempt() {
+                    return ServerMessage::Lost {
+                        code: session.game.code.clone(),
+                    };
+                }
+                match session.test(&code, crit_index) {
+                    Ok(result) => results.push((crit_index, result)),
+                    Err(e) => return ServerMessage::Error(e),
+                }
+            }
+            ServerMessage::Test { results }
+        }
+        ClientMessage::Bid { code } => ServerMessage::Bid {
+            win: code == session.game.code,
+        },
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::{
+        app, missing_session, serve, BidResult, ClientMessage, GeneratedGame, RevealResult,
+        ServerMessage, Session, SessionStore, Stats, StatusCode, TestResults, MAX_BIDS,
+        SESSION_HEADER,
+    };
+    use enigmind_lib::{
+        code::Code,
+        setup::{generate_game_seeded, PublicGame},
+    };
+    use futures_util::{SinkExt, StreamExt};
+    use std::{
+        net::{SocketAddr, TcpListener},
+        time::{Duration, Instant},
+    };
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+    fn spawn_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app().into_make_service_with_connect_info::<SocketAddr>());
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let addr = spawn_server();
+
+        let response = reqwest::get(format!("http://{addr}/ping")).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.json::<String>().await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, async {
+            stopped.await.ok();
+        }));
+
+        let response = reqwest::get(format!("http://{addr}/ping")).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        stop.send(()).unwrap();
+        let served = tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(served.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_difficulty() {
+        let addr = spawn_server();
+
+        let response = reqwest::get(format!(
+            "http://{addr}/generate?base=5&column_count=3&difficulty=40&seed=1"
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let generated: GeneratedGame = response.json().await.unwrap();
+        assert_eq!(generated.game.configuration.min_difficulty, 40);
+    }
+
+    fn has_field(value: &serde_json::Value, name: &str) -> bool {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.contains_key(name) || map.values().any(|value| has_field(value, name))
+            }
+            serde_json::Value::Array(values) => values.iter().any(|value| has_field(value, name)),
+            _ => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_hides_code() {
+        let addr = spawn_server();
+
+        let response = reqwest::get(format!("http://{addr}/generate?seed=1"))
+            .await
+            .unwrap();
+
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert!(body["session"].is_string());
+        // Intersecting the verifiers would give the code away just as well
+        for field in ["code", "verif", "mask"] {
+            assert!(!has_field(&body, field), "{field} is sent");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_binary() {
+        let addr = spawn_server();
+        let get = |query: &'static str| async move {
+            reqwest::get(format!("http://{addr}/generate?seed=1{query}"))
+                .await
+                .unwrap()
+        };
+
+        let json = get("").await.bytes().await.unwrap();
+        let response = get("&format=bin").await;
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().contains_key(SESSION_HEADER));
+        let bytes = response.bytes().await.unwrap();
+
+        assert!(bytes.len() < json.len());
+        // Field names are encoded, none of them giving the verifiers away
+        for hidden in [&b"verif"[..], b"mask"] {
+            assert!(!bytes.windows(hidden.len()).any(|window| window == hidden));
+        }
+        let game = PublicGame::from_bytes(&bytes).unwrap();
+        let expected = generate_game_seeded(5, 3, 0, 1).unwrap();
+        assert_eq!(game.criterias.len(), expected.criterias.len());
+        for (crit, expected) in game.criterias.iter().zip(expected.criterias.iter()) {
+            assert_eq!(crit.description, expected.description);
+        }
+    }
+
+    async fn generated(addr: SocketAddr, query: &str) -> GeneratedGame {
+        reqwest::get(format!("http://{addr}/generate?{query}"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_test_code() {
+        let addr = spawn_server();
+        let game = generate_game_seeded(5, 3, 0, 1).unwrap();
+        let session = generated(addr, "seed=1").await.session;
+        let test =
+            |query: &str| reqwest::get(format!("http://{addr}/test?session={session}&{query}"));
+
+        let response = test("code=123&crits=0,1").await.unwrap();
+        assert_eq!(response.status(), 200);
+        let TestResults { results, lost } = response.json().await.unwrap();
+        assert!(lost.is_none());
+        let expected: Vec<(u8, bool)> = (0..2)
+            .map(|crit| {
+                let rule = &game.criterion(crit).unwrap().verif.rule;
+                (crit, rule.evaluate(Code::new(vec![1, 2, 3])).unwrap())
+            })
+            .collect();
+        assert_eq!(results, expected);
+
+        for query in ["code=123&crits=99", "code=123&crits=x", "code=12&crits=0"] {
+            assert_eq!(test(query).await.unwrap().status(), 400);
+        }
+        let response = reqwest::get(format!(
+            "http://{addr}/test?session=unknown&code=123&crits=0"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_test_attempts_are_limited() {
+        let addr = spawn_server();
+        let code = generate_game_seeded(5, 3, 0, 1).unwrap().code;
+        let session = generated(addr, "seed=1&max_attempts=3").await.session;
+        let test =
+            |query: &str| reqwest::get(format!("http://{addr}/test?session={session}&{query}"));
+
+        // A test naming an unknown criteria is rejected before any attempt is counted
+        assert_eq!(test("code=123&crits=0,99").await.unwrap().status(), 400);
+
+        let TestResults { results, lost } = test("code=123&crits=0,1")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(lost.is_none());
+
+        // The third test is allowed, the fourth goes over and loses the game
+        let TestResults { results, lost } = test("code=123&crits=0,1")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(lost, Some(code.clone()));
+
+        assert_eq!(test("code=123&crits=0").await.unwrap().status(), 404);
+        let response = reqwest::get(format!("http://{addr}/bid?session={session}&code={code}"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_bid() {
+        let addr = spawn_server();
+        let code = generate_game_seeded(5, 3, 0, 1).unwrap().code;
+        let wrong_code = Code::new(code.0.iter().map(|digit| (digit + 1) % 5).collect());
+
+        let session = generated(addr, "seed=1").await.session;
+        let bid =
+            |code: &Code| reqwest::get(format!("http://{addr}/bid?session={session}&code={code}"));
+
+        let response = bid(&wrong_code).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let result: BidResult = response.json().await.unwrap();
+        assert!(!result.win);
+        assert_eq!(result.bids_left, MAX_BIDS - 1);
+        assert!(result.code.is_none());
+
+        // Malformed bids are rejected without being counted
+        for guess in ["12", "1234", "129"] {
+            let response =
+                reqwest::get(format!("http://{addr}/bid?session={session}&code={guess}"))
+                    .await
+                    .unwrap();
+            assert_eq!(response.status(), 400);
+        }
+
+        let result: BidResult = bid(&code).await.unwrap().json().await.unwrap();
+        assert!(result.win);
+        assert_eq!(result.bids_left, MAX_BIDS - 2);
+
+        // A won game is over
+        assert_eq!(bid(&code).await.unwrap().status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_bids_are_limited() {
+        let addr = spawn_server();
+        let code = generate_game_seeded(5, 3, 0, 1).unwrap().code;
+        let wrong_code = Code::new(code.0.iter().map(|digit| (digit + 1) % 5).collect());
+
+        let session = generated(addr, "seed=1").await.session;
+        let bid =
+            |code: &Code| reqwest::get(format!("http://{addr}/bid?session={session}&code={code}"));
+
+        for bids_left in (0..MAX_BIDS).rev() {
+            let result: BidResult = bid(&wrong_code).await.unwrap().json().await.unwrap();
+            assert!(!result.win);
+            assert_eq!(result.bids_left, bids_left);
+            // The code is only revealed once the game is lost
+            let revealed = (bids_left == 0).then(|| code.clone());
+            assert_eq!(result.code, revealed);
+        }
+
+        // Not even the right code can be tried once the bids are spent
+        assert_eq!(bid(&code).await.unwrap().status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_reveal() {
+        let addr = spawn_server();
+        let game = generate_game_seeded(5, 3, 0, 1).unwrap();
+        let session = generated(addr, "seed=1&reveal_budget=1").await.session;
+        let reveal = |crit: u8| {
+            reqwest::get(format!(
+                "http://{addr}/reveal?session={session}&crit={crit}"
+            ))
+        };
+
+        let result: RevealResult = reveal(0).await.unwrap().json().await.unwrap();
+        assert!(result.rule == game.criterion(0).unwrap().verif.rule);
+        assert_eq!(result.reveals_left, 0);
+
+        // Revealing the same criteria again is free, another one needs a reveal left
+        let result: RevealResult = reveal(0).await.unwrap().json().await.unwrap();
+        assert_eq!(result.reveals_left, 0);
+        assert_eq!(reveal(1).await.unwrap().status(), 403);
+        assert_eq!(reveal(99).await.unwrap().status(), 400);
+
+        // Games come without reveals unless asked for
+        let session = generated(addr, "seed=1").await.session;
+        let response = reqwest::get(format!("http://{addr}/reveal?session={session}&crit=0"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+    }
+
+    #[test]
+    fn test_expired_sessions_are_reported() {
+        let mut store = SessionStore::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+        let session = || Session::new(generate_game_seeded(4, 2, 0, 1).unwrap());
+        store.insert("first".to_string(), session(), now);
+        store.insert(
+            "second".to_string(),
+            session(),
+            now + Duration::from_secs(1),
+        );
+
+        let error = missing_session(&store, "first");
+        assert_eq!(error.status, StatusCode::GONE);
+        assert_eq!(error.code, "session_expired");
+        assert_eq!(missing_session(&store, "unknown").code, "unknown_session");
+    }
+
+    #[test]
+    fn test_session_caches_results() {
+        let mut session = Session::new(generate_game_seeded(5, 3, 0, 1).unwrap());
+        let code = Code::new(vec![1, 2, 3]);
+        let expected = session.game.criterias[0]
+            .verif
+            .rule
+            .evaluate(code.clone())
+            .unwrap();
+
+        assert_eq!(session.test(&code, 0), Ok(expected));
+        assert_eq!(session.hits, 0);
+        assert_eq!(session.test(&code, 0), Ok(expected));
+        assert_eq!(session.hits, 1);
+        assert_eq!(session.cache_size(), 1);
+        assert_eq!(session.hit_rate(), 0.5);
+
+        assert!(session.test(&code, 99).is_err());
+        assert_eq!(session.cache_size(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_generate_base_9() {
+        let addr = spawn_server();
+
+        let response = reqwest::get(format!(
+            "http://{addr}/generate?base=9&column_count=2&seed=1"
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let generated: GeneratedGame = response.json().await.unwrap();
+        assert_eq!(generated.game.configuration.base, 9);
+    }
+
+    #[tokio::test]
+    async fn test_generate_out_of_range() {
+        let addr = spawn_server();
+
+        for query in [
+            "base=10&column_count=3",
+            "base=1&column_count=3",
+            "base=9&column_count=5",
+        ] {
+            let response = reqwest::get(format!("http://{addr}/generate?{query}"))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), 400);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seeded_games_are_cached() {
+        let addr = spawn_server();
+        let query = "base=5&column_count=3&difficulty=10&seed=42";
+
+        let mut sessions = Vec::new();
+        for _ in 0..3 {
+            let generated: GeneratedGame = reqwest::get(format!("http://{addr}/generate?{query}"))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            sessions.push(generated);
+        }
+        reqwest::get(format!("http://{addr}/generate?base=5&column_count=3"))
+            .await
+            .unwrap();
+
+        let stats: Stats = reqwest::get(format!("http://{addr}/stats"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stats.sessions, 4);
+        assert_eq!(stats.cache.generations, 2);
+        assert_eq!((stats.cache.hits, stats.cache.misses), (2, 1));
+        assert_eq!(stats.cache.size, 1);
+        assert_ne!(sessions[0].session, sessions[1].session);
+        assert_eq!(
+            sessions[0].game.criterias.len(),
+            sessions[2].game.criterias.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_rejects_bursts() {
+        let addr = spawn_server();
+
+        let mut statuses = Vec::new();
+        for _ in 0..25 {
+            let response = reqwest::get(format!("http://{addr}/generate?base=1"))
+                .await
+                .unwrap();
+            statuses.push(response.status());
+        }
+        assert!(statuses[..20].iter().all(|status| *status == 400));
+        assert!(statuses[20..].iter().all(|status| *status == 429));
+
+        // Other routes aren't limited
+        let response = reqwest::get(format!("http://{addr}/ping")).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        let stats: Stats = reqwest::get(format!("http://{addr}/stats"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stats.rate_limited, 5);
+    }
+
+    /// Value of the sample with the given name and labels, as written in the scrape.
+    fn metric(text: &str, sample: &str) -> f64 {
+        text.lines()
+            .find_map(|line| line.strip_prefix(sample)?.strip_prefix(' '))
+            .map_or(0.0, |value| value.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let addr = spawn_server();
+        let scrape = || async {
+            let response = reqwest::get(format!("http://{addr}/metrics"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            response.text().await.unwrap()
+        };
+
+        let before = scrape().await;
+        assert_eq!(metric(&before, "enigmind_games_generated_total"), 0.0);
+
+        reqwest::get(format!(
+            "http://{addr}/generate?base=4&column_count=2&seed=3"
+        ))
+        .await
+        .unwrap();
+        let after = scrape().await;
+
+        assert_eq!(metric(&after, "enigmind_games_generated_total"), 1.0);
+        assert_eq!(
+            metric(&after, "enigmind_games_generated_by_base_total{base=\"4\"}"),
+            1.0
+        );
+        assert_eq!(
+            metric(
+                &after,
+                "enigmind_games_generated_by_column_count_total{column_count=\"2\"}"
+            ),
+            1.0
+        );
+        assert_eq!(
+            metric(&after, "enigmind_generation_duration_seconds_count"),
+            1.0
+        );
+        assert_eq!(
+            metric(
+                &after,
+                "enigmind_generation_duration_seconds_bucket{le=\"+Inf\"}"
+            ),
+            1.0
+        );
+        assert!(!after.contains("enigmind_generation_failures_total{"));
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_structured() {
+        let addr = spawn_server();
+
+        let response = reqwest::get(format!("http://{addr}/generate?base=200&column_count=9"))
+            .await
+            .unwrap();
+        assert_ne!(response.status(), 200);
+        let error: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(error["code"], "invalid_parameters");
+        assert!(error["message"].is_string());
+
+        let response = reqwest::get(format!("http://{addr}/forfeit?session=unknown"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        let error: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(error["code"], "unknown_session");
+    }
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn send(ws: &mut Client, message: &ClientMessage) {
+        let text = serde_json::to_string(message).unwrap();
+        ws.send(Message::Text(text)).await.unwrap();
+    }
+
+    async fn receive(ws: &mut Client) -> ServerMessage {
+        let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        assert!(!text.contains("\"code\""));
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_play_full_round() {
+        let addr = spawn_server();
+
+        let url = format!("ws://{addr}/play?base=3&column_count=3&seed=2");
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let ServerMessage::Start {
+            configuration,
+            criterias,
+            ..
+        } = receive(&mut ws).await
+        else {
+            panic!("expected a start message");
+        };
+
+        // Without the code, the solution is the only code every criteria accepts
+        let crits: Vec<u8> = criterias.iter().map(|crit| crit.id).collect();
+        let mut solution = None;
+        for code in configuration.codes() {
+            let crits = crits.clone();
+            send(
+                &mut ws,
+                &ClientMessage::Test {
+                    code: code.clone(),
+                    crits,
+                },
+            )
+            .await;
+
+            let ServerMessage::Test { results } = receive(&mut ws).await else {
+                panic!("expected a test result");
+            };
+            if results.iter().all(|(_, result)| *result) {
+                solution = Some(code);
+                break;
+            }
+        }
+
+        let code = solution.unwrap();
+        send(&mut ws, &ClientMessage::Bid { code }).await;
+        assert!(matches!(
+            receive(&mut ws).await,
+            ServerMessage::Bid { win: true }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_play_lost_after_max_attempts() {
+        let addr = spawn_server();
+        let game = generate_game_seeded(5, 3, 0, 2).unwrap();
+
+        let url = format!("ws://{addr}/play?seed=2&max_attempts=3");
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let ServerMessage::Start { configuration, .. } = receive(&mut ws).await else {
+            panic!("expected a start message");
+        };
+        assert_eq!(configuration.max_attempts, Some(3));
+
+        let invalid = ClientMessage::Test {
+            code: Code::new(vec![0, 0, 0]),
+            crits: vec![0, 99],
+        };
+        send(&mut ws, &invalid).await;
+        assert!(matches!(receive(&mut ws).await, ServerMessage::Error(_)));
+
+        let test = ClientMessage::Test {
+            code: Code::new(vec![0, 0, 0]),
+            crits: vec![0],
+        };
+        for _ in 0..3 {
+            send(&mut ws, &test).await;
+            assert!(matches!(receive(&mut ws).await, ServerMessage::Test { .. }));
+        }
+
+        send(&mut ws, &test).await;
+        let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let ServerMessage::Lost { code } = serde_json::from_str(&text).unwrap() else {
+            panic!("expected the game to be lost");
+        };
+        assert_eq!(code, game.code);
+    }
+
+    #[tokio::test]
+    async fn test_forfeit() {
+        let addr = spawn_server();
+        let code = generate_game_seeded(5, 3, 0, 1).unwrap().code;
+
+        let generated: GeneratedGame = reqwest::get(format!("http://{addr}/generate?seed=1"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let forfeit = || {
+            reqwest::get(format!(
+                "http://{addr}/forfeit?session={}",
+                generated.session
+            ))
+        };
+
+        let response = forfeit().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.json::<Code>().await.unwrap(), code);
+        assert_eq!(forfeit().await.unwrap().status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_schema() {
+        let addr = spawn_server();
+        let get_json = |path: &'static str| async move {
+            reqwest::get(format!("http://{addr}{path}"))
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        };
+
+        let schema = get_json("/schema").await;
+        let generated = get_json("/generate?base=4&column_count=2&seed=3").await;
+
+        let compiled = jsonschema::JSONSchema::compile(&schema).unwrap();
+        assert!(compiled.is_valid(&generated));
+        assert!(!compiled.is_valid(&serde_json::json!({ "session": 1 })));
 
-    match generate_game(base, column_count, difficulty_pct) {
-        Ok(game) => Json(game).into_response(),
-        Err(e) => Json(e.to_string()).into_response(),
+        // The schema describes the public game only
+        let criteria = &schema["definitions"]["PublicCriteria"]["properties"];
+        assert!(criteria.get("rules").is_some());
+        assert!(criteria.get("verif").is_none());
+        assert!(schema["definitions"].get("Verifier").is_none());
     }
 }
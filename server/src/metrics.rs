@@ -0,0 +1,169 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Arc, time::Duration};
+
+use tokio::sync::Mutex;
+
+pub type SharedMetrics = Arc<Mutex<Metrics>>;
+
+/// Upper bounds of the generation duration histogram buckets, in seconds.
+const DURATION_BUCKETS: [f64; 8] = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Counters on game generation, rendered in the Prometheus text format.
+#[derive(Default)]
+pub struct Metrics {
+    generated: u64,
+    by_base: BTreeMap<u8, u64>,
+    by_column_count: BTreeMap<u8, u64>,
+    // Per bucket, generations which took at most its bound
+    duration_buckets: [u64; DURATION_BUCKETS.len()],
+    duration_sum: f64,
+    duration_count: u64,
+    // Keyed by API error code, one per error variant
+    failures: BTreeMap<&'static str, u64>,
+}
+
+impl Metrics {
+    /// Counts a generation, successful or not, and how long it took.
+    pub fn record_generation(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bound, count) in DURATION_BUCKETS
+            .iter()
+            .zip(self.duration_buckets.iter_mut())
+        {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.duration_sum += seconds;
+        self.duration_count += 1;
+    }
+
+    pub fn record_game(&mut self, base: u8, column_count: u8) {
+        self.generated += 1;
+        *self.by_base.entry(base).or_default() += 1;
+        *self.by_column_count.entry(column_count).or_default() += 1;
+    }
+
+    pub fn record_failure(&mut self, code: &'static str) {
+        *self.failures.entry(code).or_default() += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        // Writing to a String can't fail
+        let _ = self.write(&mut text);
+        text
+    }
+
+    fn write(&self, text: &mut String) -> std::fmt::Result {
+        writeln!(
+            text,
+            "# HELP enigmind_games_generated_total Games generated."
+        )?;
+        writeln!(text, "# TYPE enigmind_games_generated_total counter")?;
+        writeln!(text, "enigmind_games_generated_total {}", self.generated)?;
+
+        writeln!(
+            text,
+            "# HELP enigmind_games_generated_by_base_total Games generated per base."
+        )?;
+        writeln!(
+            text,
+            "# TYPE enigmind_games_generated_by_base_total counter"
+        )?;
+        for (base, count) in &self.by_base {
+            writeln!(
+                text,
+                "enigmind_games_generated_by_base_total{{base=\"{base}\"}} {count}"
+            )?;
+        }
+
+        writeln!(
+            text,
+            "# HELP enigmind_games_generated_by_column_count_total Games generated per column count."
+        )?;
+        writeln!(
+            text,
+            "# TYPE enigmind_games_generated_by_column_count_total counter"
+        )?;
+        for (column_count, count) in &self.by_column_count {
+            writeln!(
+                text,
+                "enigmind_games_generated_by_column_count_total{{column_count=\"{column_count}\"}} {count}"
+            )?;
+        }
+
+        writeln!(
+            text,
+            "# HELP enigmind_generation_duration_seconds Time taken to generate a game."
+        )?;
+        writeln!(
+            text,
+            "# TYPE enigmind_generation_duration_seconds histogram"
+        )?;
+        for (bound, count) in DURATION_BUCKETS.iter().zip(self.duration_buckets.iter()) {
+            writeln!(
+                text,
+                "enigmind_generation_duration_seconds_bucket{{le=\"{bound}\"}} {count}"
+            )?;
+        }
+        writeln!(
+            text,
+            "enigmind_generation_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+            self.duration_count
+        )?;
+        writeln!(
+            text,
+            "enigmind_generation_duration_seconds_sum {}",
+            self.duration_sum
+        )?;
+        writeln!(
+            text,
+            "enigmind_generation_duration_seconds_count {}",
+            self.duration_count
+        )?;
+
+        writeln!(
+            text,
+            "# HELP enigmind_generation_failures_total Generations which failed, per error."
+        )?;
+        writeln!(text, "# TYPE enigmind_generation_failures_total counter")?;
+        for (code, count) in &self.failures {
+            writeln!(
+                text,
+                "enigmind_generation_failures_total{{error=\"{code}\"}} {count}"
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render() {
+        let mut metrics = Metrics::default();
+        metrics.record_generation(Duration::from_millis(30));
+        metrics.record_game(5, 3);
+        metrics.record_generation(Duration::from_secs(3));
+        metrics.record_failure("no_unique_solution");
+
+        let text = metrics.render();
+        for line in [
+            "enigmind_games_generated_total 1",
+            "enigmind_games_generated_by_base_total{base=\"5\"} 1",
+            "enigmind_games_generated_by_column_count_total{column_count=\"3\"} 1",
+            "enigmind_generation_duration_seconds_bucket{le=\"0.01\"} 0",
+            "enigmind_generation_duration_seconds_bucket{le=\"0.05\"} 1",
+            "enigmind_generation_duration_seconds_bucket{le=\"5\"} 2",
+            "enigmind_generation_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "enigmind_generation_duration_seconds_count 2",
+            "enigmind_generation_failures_total{error=\"no_unique_solution\"} 1",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line}");
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Mutex;
+
+use crate::ApiError;
+
+pub type Limiter = Arc<Mutex<RateLimiter>>;
+
+/// Allows each client address a number of requests per fixed window of time.
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    // Start of the current window and requests made in it
+    clients: HashMap<IpAddr, (Instant, u32)>,
+    rejected: u64,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            clients: HashMap::new(),
+            rejected: 0,
+        }
+    }
+
+    pub fn allow(&mut self, ip: IpAddr, now: Instant) -> bool {
+        let window = self.window;
+        self.clients
+            .retain(|_, (start, _)| now.duration_since(*start) < window);
+
+        let (_, count) = self.clients.entry(ip).or_insert((now, 0));
+        if *count >= self.limit {
+            self.rejected += 1;
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(20, Duration::from_secs(60))
+    }
+}
+
+/// Rejects requests from clients over their limit with a 429, before they reach the handler.
+pub async fn rate_limit<B>(request: Request<B>, next: Next<B>) -> Response {
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    if let Some(limiter) = request.extensions().get::<Limiter>() {
+        if !limiter.lock().await.allow(ip, Instant::now()) {
+            return ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                "Too many requests, try again later",
+            )
+            .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_allow() {
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        assert!(limiter.allow(client, now));
+        assert!(limiter.allow(client, now));
+        assert!(!limiter.allow(client, now));
+        assert!(limiter.allow(other, now));
+        assert!(limiter.allow(client, now + Duration::from_secs(10)));
+        assert_eq!(limiter.rejected(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+use crate::Session;
+
+pub type Sessions = Arc<Mutex<SessionStore>>;
+
+/// Games being played, dropped once left idle for too long or, the longest idle first, to make
+/// room for new ones.
+pub struct SessionStore {
+    capacity: usize,
+    ttl: Duration,
+    // Each session with when it was last used
+    sessions: HashMap<String, (Session, Instant)>,
+    // Dropped sessions, remembered up to the capacity to tell players theirs expired
+    expired: HashSet<String>,
+    expired_order: VecDeque<String>,
+}
+
+impl SessionStore {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            sessions: HashMap::new(),
+            expired: HashSet::new(),
+            expired_order: VecDeque::new(),
+        }
+    }
+
+    /// Adds a session, evicting the least recently used one when full.
+    pub fn insert(&mut self, id: String, session: Session, now: Instant) {
+        self.evict_idle(now);
+        if self.capacity == 0 {
+            return;
+        }
+        if self.sessions.len() >= self.capacity {
+            let oldest = self
+                .sessions
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                self.sessions.remove(&oldest);
+                self.expire(oldest);
+            }
+        }
+        self.sessions.insert(id, (session, now));
+    }
+
+    pub fn get_mut(&mut self, id: &str, now: Instant) -> Option<&mut Session> {
+        self.evict_idle(now);
+        let (session, last_used) = self.sessions.get_mut(id)?;
+        *last_used = now;
+        Some(session)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Session> {
+        self.sessions.remove(id).map(|(session, _)| session)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether the session was dropped by the store, rather than never existing or ending.
+    pub fn is_expired(&self, id: &str) -> bool {
+        self.expired.contains(id)
+    }
+
+    fn evict_idle(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        let idle: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|(_, (_, last_used))| now.duration_since(*last_used) >= ttl)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in idle {
+            self.sessions.remove(&id);
+            self.expire(id);
+        }
+    }
+
+    fn expire(&mut self, id: String) {
+        if self.expired.insert(id.clone()) {
+            self.expired_order.push_back(id);
+        }
+        while self.expired_order.len() > self.capacity {
+            if let Some(oldest) = self.expired_order.pop_front() {
+                self.expired.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new(1024, Duration::from_secs(60 * 60))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::SessionStore;
+    use crate::Session;
+    use enigmind_lib::setup::generate_game_seeded;
+    use std::time::{Duration, Instant};
+
+    fn session() -> Session {
+        Session::new(generate_game_seeded(4, 2, 0, 1).unwrap())
+    }
+
+    #[test]
+    fn test_evicts_idle_sessions() {
+        let mut store = SessionStore::new(10, Duration::from_secs(60));
+        let now = Instant::now();
+        store.insert("idle".to_string(), session(), now);
+        store.insert("used".to_string(), session(), now);
+
+        assert!(store
+            .get_mut("used", now + Duration::from_secs(40))
+            .is_some());
+        assert!(store
+            .get_mut("idle", now + Duration::from_secs(70))
+            .is_none());
+        assert!(store
+            .get_mut("used", now + Duration::from_secs(70))
+            .is_some());
+        assert_eq!(store.len(), 1);
+        assert!(store.is_expired("idle"));
+        assert!(!store.is_expired("used"));
+        assert!(!store.is_expired("unknown"));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut store = SessionStore::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        store.insert("first".to_string(), session(), now);
+        store.insert(
+            "second".to_string(),
+            session(),
+            now + Duration::from_secs(1),
+        );
+        store.get_mut("first", now + Duration::from_secs(2));
+
+        store.insert("third".to_string(), session(), now + Duration::from_secs(3));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.is_expired("second"));
+        assert!(store.remove("second").is_none());
+        assert!(store.remove("first").is_some());
+        assert!(store.remove("third").is_some());
+        assert_eq!(store.len(), 0);
+    }
+}